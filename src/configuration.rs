@@ -1,5 +1,4 @@
-use std::str::FromStr;
-
+use config::{builder::DefaultState, ConfigBuilder, ConfigError};
 use serde::Deserialize;
 use surrealdb::{
     engine::any::{self, Any},
@@ -7,7 +6,11 @@ use surrealdb::{
     Surreal,
 };
 
-use crate::models::{datastore::Datastore, p2p::PeerAddress};
+use crate::{
+    models::{datastore::Datastore, p2p::PeerAddress, AccountId},
+    network::Network,
+    Active,
+};
 
 pub fn get_configuration() -> Result<Settings, config::ConfigError> {
     // Get the base execution director
@@ -15,43 +18,72 @@ pub fn get_configuration() -> Result<Settings, config::ConfigError> {
     // Set the configuration file
     let configuration_file = "configuration.yml";
 
-    let settings = config::Config::builder()
-        //add values from a file
-        .add_source(config::File::from(base_path.join(configuration_file)))
-        .add_source(
-            config::Environment::with_prefix("APP")
-                .prefix_separator("_")
-                .separator("__"),
-        )
-        .build()?;
+    let sources = || {
+        config::Config::builder()
+            //add values from a file
+            .add_source(config::File::from(base_path.join(configuration_file)))
+            .add_source(
+                config::Environment::with_prefix("APP")
+                    .prefix_separator("_")
+                    .separator("__"),
+            )
+    };
+
+    // The network decides the defaults of other settings, so it has to be read first
+    let network = match sources().build()?.get::<Network>("node.network") {
+        Err(ConfigError::NotFound(_)) => Network::default(),
+        network => network?,
+    };
+    let settings = sources().add_network_defaults(network)?.build()?;
 
     let settings: Result<Settings, config::ConfigError> = settings.try_deserialize();
     tracing::debug!("Settings values: {:#?}", &settings);
     settings
 }
 
-#[allow(dead_code)]
 trait ConfigBuilderExtensions {
-    fn add_defaults(self) -> Result<Self, config::ConfigError>
+    fn add_network_defaults(self, network: Network) -> Result<Self, config::ConfigError>
     where
         Self: Sized;
 }
 
+impl ConfigBuilderExtensions for ConfigBuilder<DefaultState> {
+    fn add_network_defaults(self, network: Network) -> Result<Self, config::ConfigError> {
+        self.set_default("p2p.network_name", network.network_name())?
+            .set_default("p2p.bootstrap_peers", network.bootstrap_peers().to_vec())?
+            .set_default("srs_api.listen_port", network.default_port())
+    }
+}
+
 /// Settings for the node.
 #[derive(Clone, Debug, Deserialize)]
 pub struct Settings {
     pub srs_api: SrsApiSettings,
     pub database: DatabaseSettings,
-    //pub historical_moments: HistoricalMoments,
+    #[serde(default)]
+    pub historical_moments: HistoricalMoments,
     pub node: NodeSettings,
     pub p2p: PeerToPeerSettings,
 }
 
+impl Settings {
+    /// Returns the fork schedule of the configured network with the configured overrides
+    /// applied.
+    pub fn historical_moments(&self) -> crate::HistoricalMoments<Active> {
+        self.node
+            .network
+            .historical_moments()
+            .with_overrides(&self.historical_moments)
+            .build()
+    }
+}
+
 /// Settings for the signum-style API.
 #[derive(Clone, Debug, Deserialize)]
 pub struct SrsApiSettings {
     pub base_url: String,
     pub listen_address: String,
+    /// Defaults to the network's port.
     pub listen_port: u16,
 }
 
@@ -104,90 +136,73 @@ async fn initialize_database(db: Surreal<Any>) -> Result<Surreal<Any>, anyhow::E
     )
     .await?;
 
+    tracing::info!("Defining indexes on block_id, height and generator_id fields");
+    db.query(
+        r#"
+            DEFINE INDEX unique_block_id ON block COLUMNS block_id UNIQUE;
+            DEFINE INDEX unique_block_height ON block COLUMNS height UNIQUE;
+            DEFINE INDEX block_generator_id ON block COLUMNS generator_id;
+        "#,
+    )
+    .await?;
+
+    tracing::info!("Defining indexes on confirmed transaction lookup fields");
+    db.query(
+        r#"
+            DEFINE INDEX unique_transaction_id ON confirmed_transaction COLUMNS transaction_id UNIQUE;
+            DEFINE INDEX unique_transaction_full_hash ON confirmed_transaction COLUMNS full_hash UNIQUE;
+            DEFINE INDEX transaction_block_id ON confirmed_transaction COLUMNS block_id;
+            DEFINE INDEX transaction_sender_id ON confirmed_transaction COLUMNS sender_id;
+            DEFINE INDEX transaction_recipient_id ON confirmed_transaction COLUMNS recipient_id;
+        "#,
+    )
+    .await?;
+
     Ok(db)
 }
 
 /// This settings struct represents any overrides for the historical moments. All values are optional.
-#[derive(Clone, Debug, Deserialize)]
+/// Unset values fall back to the mainnet heights, see [`crate::HistoricalMoments`].
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(default)]
 pub struct HistoricalMoments {
-    #[serde(default = "HistoricalMoments::genesis")]
-    pub genesis: u32,
-    #[serde(default = "HistoricalMoments::reward_recipient_enable")]
-    pub reward_recipient_enable: u32,
-    #[serde(default = "HistoricalMoments::digital_goods_store_enable")]
-    pub digital_goods_store_enable: u32,
-    #[serde(default = "HistoricalMoments::automated_transaction_enable")]
-    pub automated_transaction_enable: u32,
-    #[serde(default = "HistoricalMoments::automated_transaction_fix_1")]
-    pub automated_transaction_fix_1: u32,
-    #[serde(default = "HistoricalMoments::automated_transaction_fix_2")]
-    pub automated_transaction_fix_2: u32,
-    #[serde(default = "HistoricalMoments::automated_transaction_fix_3")]
-    pub automated_transaction_fix_3: u32,
-    #[serde(default = "HistoricalMoments::pre_poc2")]
-    pub pre_poc2: u32,
-    #[serde(default = "HistoricalMoments::poc2_enable")]
-    pub poc2_enable: u32,
-    #[serde(default = "HistoricalMoments::sodium_enable")]
-    pub sodium_enable: u32,
-    #[serde(default = "HistoricalMoments::signum_name_change")]
-    pub signum_name_change: u32,
-    #[serde(default = "HistoricalMoments::poc_plus_enable")]
-    pub poc_plus_enable: u32,
-    #[serde(default = "HistoricalMoments::speedway_enable")]
-    pub speedway_enable: u32,
-    #[serde(default = "HistoricalMoments::smart_token_enable")]
-    pub smart_token_enable: u32,
-    #[serde(default = "HistoricalMoments::smart_fees_enable")]
-    pub smart_fees_enable: u32,
-    #[serde(default = "HistoricalMoments::smart_ats_enable")]
-    pub smart_ats_enable: u32,
-    #[serde(default = "HistoricalMoments::automated_transaction_fix_4")]
-    pub automated_transaction_fix_4: u32,
-    #[serde(default = "HistoricalMoments::distribution_fix_enable")]
-    pub distribution_fix_enable: u32,
-    #[serde(default = "HistoricalMoments::pk_freeze")]
-    pub pk_freeze: u32,
-    #[serde(default = "HistoricalMoments::pk_freeze_2")]
-    pub pk_freeze_2: u32,
-    #[serde(default = "HistoricalMoments::smart_alias_enable")]
-    pub smart_alias_enable: u32,
-    #[serde(default = "HistoricalMoments::next_fork")]
-    pub next_fork: u32,
-    //pub reward_recipient_enable: Option<u32>,
-    //pub digital_goods_store_enable: Option<u32>,
-    //pub automated_transaction_enable: Option<u32>,
-    //pub automated_transaction_fix_1: Option<u32>,
-    //pub automated_transaction_fix_2: Option<u32>,
-    //pub automated_transaction_fix_3: Option<u32>,
-    //pub pre_poc2: Option<u32>,
-    //pub poc2_enable: Option<u32>,
-    //pub sodium_enable: Option<u32>,
-    //pub signum_name_change: Option<u32>,
-    //pub poc_plus_enable: Option<u32>,
-    //pub speedway_enable: Option<u32>,
-    //pub smart_token_enable: Option<u32>,
-    //pub smart_fees_enable: Option<u32>,
-    //pub smart_ats_enable: Option<u32>,
-    //pub automated_transaction_fix_4: Option<u32>,
-    //pub distribution_fix_enable: Option<u32>,
-    //pub pk_freeze: Option<u32>,
-    //pub pk_freeze_2: Option<u32>,
-    //pub smart_alias_enable: Option<u32>,
-    //pub next_fork: Option<u32>,
+    pub reward_recipient_enable: Option<u32>,
+    pub digital_goods_store_enable: Option<u32>,
+    pub automated_transaction_enable: Option<u32>,
+    pub automated_transaction_fix_1: Option<u32>,
+    pub automated_transaction_fix_2: Option<u32>,
+    pub automated_transaction_fix_3: Option<u32>,
+    pub pre_poc2: Option<u32>,
+    pub poc2_enable: Option<u32>,
+    pub sodium_enable: Option<u32>,
+    pub signum_name_change: Option<u32>,
+    pub poc_plus_enable: Option<u32>,
+    pub speedway_enable: Option<u32>,
+    pub smart_token_enable: Option<u32>,
+    pub smart_fees_enable: Option<u32>,
+    pub smart_ats_enable: Option<u32>,
+    pub automated_transaction_fix_4: Option<u32>,
+    pub distribution_fix_enable: Option<u32>,
+    pub pk_freeze: Option<u32>,
+    pub pk_freeze_2: Option<u32>,
+    pub smart_alias_enable: Option<u32>,
+    pub next_fork: Option<u32>,
 }
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct NodeSettings {
-    pub cash_back_id: String,
-    pub network: String,
+    /// The account that receives the cash back on transactions this node creates. Either the
+    /// numeric ID or the `S-` address.
+    pub cash_back_id: AccountId,
+    /// The network to join, which sets the defaults of the network specific settings.
+    #[serde(default)]
+    pub network: Network,
 }
 
 /// Peer to Peer settings.
 #[derive(Clone, Debug, Deserialize)]
 pub struct PeerToPeerSettings {
-    /// Peer addresses to use if none are in the database already.
-    #[serde(default = "PeerToPeerSettings::default_value_bootstrap_peers")]
+    /// Peer addresses to use if none are in the database already. Defaults to the network's.
     pub bootstrap_peers: Vec<PeerAddress>,
     /// Address that peers should attempt to connect to.
     #[serde(default = "PeerToPeerSettings::default_value_my_address")]
@@ -198,45 +213,18 @@ pub struct PeerToPeerSettings {
     /// Whether or not peers should pass along your address to their own peers.
     #[serde(default = "PeerToPeerSettings::default_value_share_address")]
     pub share_address: bool,
-    /// The name of the network to which this node is connecting.
-    #[serde(default = "PeerToPeerSettings::default_value_network_name")]
+    /// The name of the network to which this node is connecting. Defaults to the network's.
     pub network_name: String,
-    /// The address to which SNR awards should be paid. Currently unused on the network.
+    /// The account to which SNR awards should be paid. Currently unused on the network.
     #[serde(default = "PeerToPeerSettings::default_value_snr_reward_address")]
-    pub snr_reward_address: String,
+    pub snr_reward_address: Option<AccountId>,
+    /// The maximum number of peers to return when another node asks for this node's peers.
+    #[serde(default = "PeerToPeerSettings::default_value_max_shared_peers")]
+    pub max_shared_peers: u32,
 }
 
 // Defaults for PeerToPeerSettings
 impl PeerToPeerSettings {
-    fn default_value_bootstrap_peers() -> Vec<PeerAddress> {
-        vec![
-            PeerAddress::from_str("australia.signum.network:8123")
-                .expect("could not parse bootstrap ip address `australia.signum.network:8123`"),
-            PeerAddress::from_str("brazil.signum.network:8123")
-                .expect("could not parse bootstrap ip address `brazil.signum.network:8123`"),
-            PeerAddress::from_str("canada.signum.network:8123")
-                .expect("could not parse bootstrap ip address `canada.signum.network:8123`"),
-            PeerAddress::from_str("europe.signum.network:8123")
-                .expect("could not parse bootstrap ip address `europe.signum.network:8123`"),
-            PeerAddress::from_str("europe1.signum.network:8123")
-                .expect("could not parse bootstrap ip address `europe1.signum.network:8123`"),
-            PeerAddress::from_str("europe2.signum.network:8123")
-                .expect("could not parse bootstrap ip address `europe2.signum.network:8123`"),
-            PeerAddress::from_str("europe3.signum.network:8123")
-                .expect("could not parse bootstrap ip address `europe3.signum.network:8123`"),
-            PeerAddress::from_str("latam.signum.network:8123")
-                .expect("could not parse bootstrap ip address `latam.signum.network:8123`"),
-            PeerAddress::from_str("singapore.signum.network:8123")
-                .expect("could not parse bootstrap ip address `singapore.signum.network:8123`"),
-            PeerAddress::from_str("ru.signum.network:8123")
-                .expect("could not parse bootstrap ip address `ru.signum.network:8123`"),
-            PeerAddress::from_str("us-central.signum.network:8123")
-                .expect("could not parse bootstrap ip address `us-central.signum.network:8123`"),
-            PeerAddress::from_str("us-east.signum.network:8123")
-                .expect("could not parse bootstrap ip address `us-east.signum.network:8123`"),
-        ]
-    }
-
     fn default_value_my_address() -> String {
         //TODO: Figure out a way to get external IP and populate it
         String::new()
@@ -250,81 +238,11 @@ impl PeerToPeerSettings {
         true
     }
 
-    fn default_value_network_name() -> String {
-        "Signum".to_string()
-    }
-
-    fn default_value_snr_reward_address() -> String {
-        String::new()
+    fn default_value_snr_reward_address() -> Option<AccountId> {
+        None
     }
-}
 
-// Defaults for HistoricalMoments
-impl HistoricalMoments {
-    fn genesis() -> u32 {
-        0
-    }
-    fn reward_recipient_enable() -> u32 {
-        6_500
-    }
-    fn digital_goods_store_enable() -> u32 {
-        11_800
-    }
-    fn automated_transaction_enable() -> u32 {
-        49_200
-    }
-    fn automated_transaction_fix_1() -> u32 {
-        67_000
-    }
-    fn automated_transaction_fix_2() -> u32 {
-        92_000
-    }
-    fn automated_transaction_fix_3() -> u32 {
-        255_000
-    }
-    fn pre_poc2() -> u32 {
-        500_000
-    }
-    fn poc2_enable() -> u32 {
-        502_000
-    }
-    fn sodium_enable() -> u32 {
-        765_000
-    }
-    fn signum_name_change() -> u32 {
-        875_500
-    }
-    fn poc_plus_enable() -> u32 {
-        878_000
-    }
-    fn speedway_enable() -> u32 {
-        941_100
-    }
-    fn smart_token_enable() -> u32 {
-        1_029_000
-    }
-    fn smart_fees_enable() -> u32 {
-        1_029_000
-    }
-    fn smart_ats_enable() -> u32 {
-        1_029_000
-    }
-    fn automated_transaction_fix_4() -> u32 {
-        1_051_900
-    }
-    fn distribution_fix_enable() -> u32 {
-        1_051_900
-    }
-    fn pk_freeze() -> u32 {
-        1_099_400
-    }
-    fn pk_freeze_2() -> u32 {
-        1_150_000
-    }
-    fn smart_alias_enable() -> u32 {
-        1_150_000
-    }
-    fn next_fork() -> u32 {
-        u32::MAX
+    fn default_value_max_shared_peers() -> u32 {
+        100
     }
 }
@@ -0,0 +1,113 @@
+//! Signum's EC-KCDSA signatures over Curve25519, as used to sign blocks and transactions.
+//!
+//! Keys are derived from a passphrase by hashing it with SHA-256. A signature is the 32 byte
+//! value `v` followed by the 32 byte hash `h`, and verifies if recomputing `h` from the
+//! message and `v * P + h * G` gives the same hash.
+
+mod curve25519;
+
+use sha2::{Digest, Sha256};
+
+/// Returns the public key belonging to `passphrase`.
+pub fn public_key(passphrase: &str) -> [u8; 32] {
+    let mut k = Sha256::digest(passphrase.as_bytes()).into();
+    curve25519::clamp(&mut k);
+    curve25519::keygen(&k).0
+}
+
+/// Signs `message` with the key belonging to `passphrase`.
+pub fn sign(message: &[u8], passphrase: &str) -> [u8; 64] {
+    let mut k = Sha256::digest(passphrase.as_bytes()).into();
+    curve25519::clamp(&mut k);
+    let (_, s) = curve25519::keygen(&k);
+
+    let message_hash = Sha256::digest(message);
+    let mut x = Sha256::new()
+        .chain_update(message_hash)
+        .chain_update(s)
+        .finalize()
+        .into();
+    curve25519::clamp(&mut x);
+    let (y, _) = curve25519::keygen(&x);
+    let h = Sha256::new()
+        .chain_update(message_hash)
+        .chain_update(y)
+        .finalize()
+        .into();
+    let v = curve25519::sign(&h, &x, &s);
+
+    let mut signature = [0; 64];
+    signature[..32].copy_from_slice(&v);
+    signature[32..].copy_from_slice(&h);
+    signature
+}
+
+/// Returns whether `signature` is a signature of `message` by the owner of `public_key`.
+///
+/// Enforcing canonical signatures and public keys rejects the alternative encodings that
+/// would otherwise verify too, which BRS does for everything but the oldest blocks.
+pub fn verify(
+    signature: &[u8; 64],
+    message: &[u8],
+    public_key: &[u8; 32],
+    enforce_canonical: bool,
+) -> bool {
+    let v: [u8; 32] = signature[..32].try_into().expect("slice is 32 bytes");
+    let h: [u8; 32] = signature[32..].try_into().expect("slice is 32 bytes");
+    if enforce_canonical
+        && !(curve25519::is_canonical_signature(&v)
+            && curve25519::is_canonical_public_key(public_key))
+    {
+        return false;
+    }
+
+    let y = curve25519::verify(&v, &h, public_key);
+    let expected_h: [u8; 32] = Sha256::new()
+        .chain_update(Sha256::digest(message))
+        .chain_update(y)
+        .finalize()
+        .into();
+    expected_h == h
+}
+
+#[cfg(test)]
+mod test {
+    use super::{public_key, sign, verify};
+
+    #[test]
+    fn verify_accepts_signatures_made_by_sign() {
+        let signature = sign(b"message", "test passphrase");
+
+        assert!(verify(
+            &signature,
+            b"message",
+            &public_key("test passphrase"),
+            true
+        ));
+        assert!(!verify(
+            &signature,
+            b"other message",
+            &public_key("test passphrase"),
+            true
+        ));
+        assert!(!verify(
+            &signature,
+            b"message",
+            &public_key("other passphrase"),
+            true
+        ));
+    }
+
+    #[test]
+    fn verify_rejects_non_canonical_signatures() {
+        let mut signature = sign(b"message", "test passphrase");
+        signature[31] |= 0xf0;
+
+        assert!(!verify(
+            &signature,
+            b"message",
+            &public_key("test passphrase"),
+            true
+        ));
+    }
+}
@@ -0,0 +1,340 @@
+//! The Curve25519 primitives behind Signum's EC-KCDSA signatures.
+//!
+//! This follows the Java implementation by Matthijs van Duin that BRS uses, including its
+//! quirks around the sign of public keys, so that signatures verify exactly as they do on the
+//! rest of the network.
+
+use std::sync::OnceLock;
+
+use num_bigint::{BigInt, BigUint, Sign};
+
+/// The x coordinate of the base point.
+const BASE_X: u32 = 9;
+
+/// `y² = x³ + 486662x² + x`
+const CURVE_A: u32 = 486_662;
+
+/// `(A - 2) / 4`, used when doubling points.
+const A24: u32 = 121_665;
+
+/// The square of the base point's y coordinate.
+const BASE_Y_SQUARED: u32 = 39_420_360;
+
+/// The base point's y coordinate, with the sign BRS uses.
+const BASE_Y: &str =
+    "43114425171068552920764898935933967039370386198203806730763910166200978582548";
+
+/// The order of the base point, `2^252 + 27742317777372353535851937790883648493`.
+const ORDER: &str = "7237005577332262213973186563042994240857116359379907606001950938285454250989";
+
+/// The field prime, `2^255 - 19`.
+fn prime() -> &'static BigUint {
+    static PRIME: OnceLock<BigUint> = OnceLock::new();
+    PRIME.get_or_init(|| (BigUint::from(1u8) << 255) - 19u8)
+}
+
+fn order() -> &'static BigUint {
+    static ORDER_VALUE: OnceLock<BigUint> = OnceLock::new();
+    ORDER_VALUE.get_or_init(|| ORDER.parse().expect("curve order is a valid number"))
+}
+
+fn base_y() -> &'static Fe {
+    static BASE_Y_VALUE: OnceLock<Fe> = OnceLock::new();
+    BASE_Y_VALUE.get_or_init(|| Fe(BASE_Y.parse().expect("base point y is a valid number")))
+}
+
+/// An element of the field of integers modulo [`prime`].
+#[derive(Clone, Debug, PartialEq)]
+struct Fe(BigUint);
+
+impl Fe {
+    fn from_u32(value: u32) -> Self {
+        Self(BigUint::from(value))
+    }
+
+    /// Reads a little endian field element, ignoring the top bit like the Java implementation.
+    fn from_bytes(bytes: &[u8; 32]) -> Self {
+        let mut bytes = *bytes;
+        bytes[31] &= 0x7f;
+        Self(BigUint::from_bytes_le(&bytes) % prime())
+    }
+
+    fn to_bytes(&self) -> [u8; 32] {
+        to_32_bytes(&self.0)
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        Self((&self.0 + &other.0) % prime())
+    }
+
+    fn sub(&self, other: &Self) -> Self {
+        Self((&self.0 + prime() - &other.0) % prime())
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        Self((&self.0 * &other.0) % prime())
+    }
+
+    fn square(&self) -> Self {
+        self.mul(self)
+    }
+
+    fn pow(&self, exponent: &BigUint) -> Self {
+        Self(self.0.modpow(exponent, prime()))
+    }
+
+    /// Returns the multiplicative inverse, or zero for zero.
+    fn invert(&self) -> Self {
+        self.pow(&(prime() - 2u8))
+    }
+
+    /// Returns a square root if the element is a square, using the same formula as the Java
+    /// implementation so that non-squares produce the same garbage.
+    fn sqrt(&self) -> Self {
+        let double = self.add(self);
+        let v = double.pow(&((prime() - 5u8) >> 3));
+        let t = double.mul(&v.square()).sub(&Self::from_u32(1));
+        self.mul(&v).mul(&t)
+    }
+
+    fn is_negative(&self) -> bool {
+        self.0.bit(0)
+    }
+
+    fn negate(&self) -> Self {
+        Self::from_u32(0).sub(self)
+    }
+}
+
+/// Returns `y²` for the point with the given x coordinate.
+fn x_to_y2(x: &Fe) -> Fe {
+    let x2 = x.square();
+    x2.mul(x).add(&x2.mul(&Fe::from_u32(CURVE_A))).add(x)
+}
+
+/// Returns `(x + z, x - z)` for a point in projective coordinates.
+fn mont_prep(x: &Fe, z: &Fe) -> (Fe, Fe) {
+    (x.add(z), x.sub(z))
+}
+
+/// Adds the prepared points `a` and `b`, whose difference has the x coordinate `dx`.
+fn mont_add(a: &(Fe, Fe), b: &(Fe, Fe), dx: &Fe) -> (Fe, Fe) {
+    let x = a.1.mul(&b.0);
+    let z = a.0.mul(&b.1);
+    let sum = x.add(&z);
+    let difference = x.sub(&z);
+    (sum.square(), difference.square().mul(dx))
+}
+
+/// Doubles the prepared point `a`.
+fn mont_dbl(a: &(Fe, Fe)) -> (Fe, Fe) {
+    let sum = a.0.square();
+    let difference = a.1.square();
+    let x = sum.mul(&difference);
+    let difference = sum.sub(&difference);
+    let z = sum
+        .add(&difference.mul(&Fe::from_u32(A24)))
+        .mul(&difference);
+    (x, z)
+}
+
+/// Clamps a scalar the way Curve25519 private keys are clamped.
+pub(super) fn clamp(k: &mut [u8; 32]) {
+    k[31] &= 0x7f;
+    k[31] |= 0x40;
+    k[0] &= 0xf8;
+}
+
+/// Multiplies the point with x coordinate `base` by `k`, returning the x coordinates of
+/// `k * base` and `(k + 1) * base`.
+fn ladder(k: &[u8; 32], base: &Fe) -> (Fe, Fe) {
+    let mut x = [Fe::from_u32(1), base.clone()];
+    let mut z = [Fe::from_u32(0), Fe::from_u32(1)];
+    for bit in (0..256).rev() {
+        let b1 = usize::from(k[bit / 8] >> (bit % 8) & 1);
+        let b0 = 1 - b1;
+        let a = mont_prep(&x[b0], &z[b0]);
+        let b = mont_prep(&x[b1], &z[b1]);
+        (x[b0], z[b0]) = mont_add(&a, &b, base);
+        (x[b1], z[b1]) = mont_dbl(&b);
+    }
+    (x[0].mul(&z[0].invert()), x[1].mul(&z[1].invert()))
+}
+
+/// Returns the public key for the already clamped key `k`, along with the key used to sign
+/// with it.
+pub(super) fn keygen(k: &[u8; 32]) -> ([u8; 32], [u8; 32]) {
+    let base_x = Fe::from_u32(BASE_X);
+    let (px, qx) = ladder(k, &base_x);
+
+    // Recover the sign of the public key's y coordinate from P and Q = P + G
+    let py2 = x_to_y2(&px);
+    let dx = px.sub(&base_x);
+    let negative_py = qx
+        .add(&px)
+        .add(&base_x)
+        .add(&Fe::from_u32(CURVE_A))
+        .mul(&dx.square())
+        .sub(&py2)
+        .sub(&Fe::from_u32(BASE_Y_SQUARED))
+        .mul(&base_y().add(base_y()).invert());
+
+    let k = BigUint::from_bytes_le(k) % order();
+    let s = match negative_py.is_negative() {
+        true => k,
+        false => (order() - k) % order(),
+    };
+    let s = mod_inverse(&s, order());
+
+    (px.to_bytes(), to_32_bytes(&s))
+}
+
+/// Returns `(x - h) * s mod q`, the first half of a signature.
+pub(super) fn sign(h: &[u8; 32], x: &[u8; 32], s: &[u8; 32]) -> [u8; 32] {
+    let h = BigUint::from_bytes_le(h) % order();
+    let x = BigUint::from_bytes_le(x) % order();
+    let s = BigUint::from_bytes_le(s);
+    let v = (x + order() - h) * s % order();
+    to_32_bytes(&v)
+}
+
+/// Returns the x coordinate of `v * abs(P) + h * G`, where `abs(P)` is the point with the x
+/// coordinate `p` and an even y coordinate.
+pub(super) fn verify(v: &[u8; 32], h: &[u8; 32], p: &[u8; 32]) -> [u8; 32] {
+    let base_x = Fe::from_u32(BASE_X);
+    let px = Fe::from_bytes(p);
+    let points = [base_x.clone(), px.clone()];
+
+    // s[0] = X(abs(P) + G) and s[1] = X(abs(P) - G)
+    let py2 = x_to_y2(&px);
+    let py = py2.sqrt();
+    let py = match py.is_negative() {
+        true => py.negate(),
+        false => py,
+    };
+    let squares = py2.add(&Fe::from_u32(BASE_Y_SQUARED));
+    let cross = base_y().add(base_y()).mul(&py);
+    let dx_inverse = px.sub(&base_x).square().invert();
+    let offset = px.add(&base_x).add(&Fe::from_u32(CURVE_A));
+    let sums = [
+        squares.sub(&cross).mul(&dx_inverse).sub(&offset),
+        squares.add(&cross).mul(&dx_inverse).sub(&offset),
+    ];
+
+    // Prepare the chain of which point to add at each step
+    let mut d = [0u8; 32];
+    let (mut vi, mut hi, mut di, mut nvh) = (0i32, 0i32, 0i32, 0i32);
+    for i in 0..32 {
+        vi = (vi >> 8) ^ i32::from(v[i]) ^ (i32::from(v[i]) << 1);
+        hi = (hi >> 8) ^ i32::from(h[i]) ^ (i32::from(h[i]) << 1);
+        nvh = !(vi ^ hi);
+        di = (nvh & (di & 0x80) >> 7) ^ vi;
+        for bit in 0..7 {
+            di ^= nvh & (di & (1 << bit)) << 1;
+        }
+        d[i] = di as u8;
+    }
+    di = ((nvh & (di & 0x80) << 1) ^ vi) >> 8;
+
+    // y[0] is (even)P + (even)G
+    // y[1] is (even)P + (odd)G if the current d bit is 0, and (odd)P + (even)G if it's 1
+    // y[2] is (odd)P + (odd)G
+    let mut yx = [
+        Fe::from_u32(1),
+        points[di as usize].clone(),
+        sums[0].clone(),
+    ];
+    let mut yz = [Fe::from_u32(0), Fe::from_u32(1), Fe::from_u32(1)];
+    let (mut vi, mut hi) = (0i32, 0i32);
+    for i in (0..32).rev() {
+        vi = (vi << 8) | i32::from(v[i]);
+        hi = (hi << 8) | i32::from(h[i]);
+        di = (di << 8) | i32::from(d[i]);
+        for j in (0..8).rev() {
+            let prepared = [
+                mont_prep(&yx[0], &yz[0]),
+                mont_prep(&yx[1], &yz[1]),
+                mont_prep(&yx[2], &yz[2]),
+            ];
+            let k = (((vi ^ vi >> 1) >> j & 1) + ((hi ^ hi >> 1) >> j & 1)) as usize;
+            (yx[0], yz[0]) = mont_dbl(&prepared[k]);
+            let k = ((di >> j & 2) ^ ((di >> j & 1) << 1)) as usize;
+            (yx[1], yz[1]) = mont_add(&prepared[1], &prepared[k], &points[(di >> j & 1) as usize]);
+            (yx[2], yz[2]) = mont_add(
+                &prepared[2],
+                &prepared[0],
+                &sums[(((vi ^ hi) >> j & 2) >> 1) as usize],
+            );
+        }
+    }
+
+    let k = ((vi & 1) + (hi & 1)) as usize;
+    yx[k].mul(&yz[k].invert()).to_bytes()
+}
+
+/// Returns whether the first half of a signature is reduced modulo the curve order.
+pub(super) fn is_canonical_signature(v: &[u8; 32]) -> bool {
+    BigUint::from_bytes_le(v) < *order()
+}
+
+/// Returns whether a public key is a fully reduced field element.
+pub(super) fn is_canonical_public_key(p: &[u8; 32]) -> bool {
+    Fe::from_bytes(p).to_bytes() == *p
+}
+
+fn mod_inverse(value: &BigUint, modulus: &BigUint) -> BigUint {
+    let modulus = BigInt::from_biguint(Sign::Plus, modulus.clone());
+    let (mut r0, mut r1) = (
+        modulus.clone(),
+        BigInt::from_biguint(Sign::Plus, value.clone()),
+    );
+    let (mut t0, mut t1) = (BigInt::from(0), BigInt::from(1));
+    while r1 != BigInt::from(0) {
+        let quotient = &r0 / &r1;
+        (r0, r1) = (r1.clone(), r0 - &quotient * r1);
+        (t0, t1) = (t1.clone(), t0 - &quotient * t1);
+    }
+    let inverse = ((t0 % &modulus) + &modulus) % &modulus;
+    inverse
+        .to_biguint()
+        .expect("inverse is reduced to a positive number")
+}
+
+fn to_32_bytes(value: &BigUint) -> [u8; 32] {
+    let mut bytes = [0; 32];
+    let le = value.to_bytes_le();
+    bytes[..le.len()].copy_from_slice(&le);
+    bytes
+}
+
+#[cfg(test)]
+mod test {
+    use super::{clamp, keygen, ladder, Fe};
+
+    fn bytes(value: &str) -> [u8; 32] {
+        hex::decode(value).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn ladder_matches_rfc_7748_test_vector() {
+        let mut k = bytes("a546e36bf0527c9d3b16154b82465edd62144c0ac1fc5a18506a2244ba449ac4");
+        clamp(&mut k);
+        let u = bytes("e6db6867583030db3594c1a424b15f7c726624ec26b3353b10a903a6d0ab1c4c");
+
+        assert_eq!(
+            hex::encode(ladder(&k, &Fe::from_bytes(&u)).0.to_bytes()),
+            "c3da55379de9c6908e94ea4df28d084f32eccf03491c71f754b4075577a28552"
+        );
+    }
+
+    #[test]
+    fn keygen_matches_rfc_7748_public_key() {
+        let mut k = bytes("77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a");
+        clamp(&mut k);
+
+        assert_eq!(
+            hex::encode(keygen(&k).0),
+            "8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a"
+        );
+    }
+}
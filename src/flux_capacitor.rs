@@ -1,54 +1,114 @@
-pub trait FluxValue<T> {
-    fn get_flux_value(height: u32) -> T;
-}
+//! Consensus values that changed at hard forks. Each is defined once, with the forks it
+//! changed at, and looked up for the height of the block being validated.
+
+use crate::{Active, Fork, HistoricalMoments};
 
+/// A change of a flux value taking effect at a fork.
 pub struct FluxChangeValue<T> {
-    pub(crate) _height: u32,
-    pub(crate) _new_value: T,
+    pub(crate) fork: Fork,
+    pub(crate) new_value: T,
 }
 impl<T> FluxChangeValue<T> {
-    pub fn new(_height: u32, _new_value: T) -> Self {
-        Self {
-            _height,
-            _new_value,
-        }
+    pub const fn new(fork: Fork, new_value: T) -> Self {
+        Self { fork, new_value }
     }
 }
 
-pub struct Flux<T> {
-    _genesis_value: T,
-    /// A vec of tuple of u32 (height) and T (the value)
-    _changes: Vec<FluxChangeValue<T>>,
+pub struct Flux<T: 'static> {
+    genesis_value: T,
+    /// The changes to the value, in the order they happened.
+    changes: &'static [FluxChangeValue<T>],
 }
-impl<T> Flux<T> {
-    pub const fn new(genesis_value: T, changes: Vec<FluxChangeValue<T>>) -> Self {
+impl<T: Copy> Flux<T> {
+    pub const fn new(genesis_value: T, changes: &'static [FluxChangeValue<T>]) -> Self {
         Self {
-            _genesis_value: genesis_value,
-            _changes: changes,
+            genesis_value,
+            changes,
         }
     }
-    pub fn get_flux_value(_height: u32) -> T {
-        todo!()
+
+    /// Returns the value in effect for the block at `height`.
+    pub fn get_flux_value(&self, height: u64, historical_moments: &HistoricalMoments<Active>) -> T {
+        self.changes
+            .iter()
+            .rev()
+            .find(|c| historical_moments.is_active(c.fork, height))
+            .map_or(self.genesis_value, |c| c.new_value)
     }
 }
 
-//pub static MAX_PAYLOAD_LENGTH: Flux<u32> = Flux::<u32>::new(
-//    255 * 176,
-//    vec![
-//        FluxChangeValue::new(historical_moments::PRE_POC2.get().unwrap(), 255 * 176 * 4),
-//        FluxChangeValue::new(
-//            historical_moments::SMART_FEES_ENABLE.get().unwrap(),
-//            255 * (176 + 8) * 4 * 2,
-//        ),
-//    ],
-//);
-//pub static MAX_PAYLOAD_LENGTH: Flux<u32> = Flux::<u32>::new(
-//    255 * 176,
-//    vec![
-//        FluxChangeValue::new(historical_moments::PRE_POC2.get().unwrap(), 255 * 176 * 4),
-//        FluxChangeValue::new(
-//            historical_moments::SMART_FEES_ENABLE.get().unwrap(),
-//            255 * (176 + 8) * 4 * 2,
-//        ),
-//    ],
-//);
+/// The target number of seconds between blocks.
+pub static BLOCK_TIME: Flux<u64> = Flux::new(240, &[]);
+
+/// The most transactions a block may hold.
+pub static MAX_NUMBER_OF_TRANSACTIONS: Flux<u32> = Flux::new(
+    255,
+    &[
+        FluxChangeValue::new(Fork::PrePoc2, 255 * 4),
+        FluxChangeValue::new(Fork::SmartFeesEnable, 255 * 4 * 2),
+    ],
+);
+
+/// The most bytes a block's transactions may take.
+pub static MAX_PAYLOAD_LENGTH: Flux<u32> = Flux::new(
+    255 * 176,
+    &[
+        FluxChangeValue::new(Fork::PrePoc2, 255 * 176 * 4),
+        FluxChangeValue::new(Fork::SmartFeesEnable, 255 * (176 + 8) * 4 * 2),
+    ],
+);
+
+/// The minimum fee a transaction must pay, in NQT.
+pub static MIN_FEE_NQT: Flux<u64> = Flux::new(
+    100_000_000,
+    &[
+        FluxChangeValue::new(Fork::PrePoc2, 735_000),
+        FluxChangeValue::new(Fork::SmartFeesEnable, 1_000_000),
+    ],
+);
+
+#[cfg(test)]
+mod test {
+    use crate::{Fork, HistoricalMoments};
+
+    use super::{MAX_PAYLOAD_LENGTH, MIN_FEE_NQT};
+
+    #[test]
+    fn get_flux_value_follows_fork_heights() {
+        let historical_moments = HistoricalMoments::new().build();
+
+        assert_eq!(
+            MIN_FEE_NQT.get_flux_value(0, &historical_moments),
+            100_000_000
+        );
+        assert_eq!(
+            MIN_FEE_NQT.get_flux_value(499_999, &historical_moments),
+            100_000_000
+        );
+        assert_eq!(
+            MIN_FEE_NQT.get_flux_value(500_000, &historical_moments),
+            735_000
+        );
+        assert_eq!(
+            MIN_FEE_NQT.get_flux_value(1_029_000, &historical_moments),
+            1_000_000
+        );
+        assert_eq!(
+            MAX_PAYLOAD_LENGTH.get_flux_value(1_200_000, &historical_moments),
+            375_360
+        );
+    }
+
+    #[test]
+    fn get_flux_value_uses_overridden_fork_heights() {
+        let historical_moments = HistoricalMoments::new()
+            .with_height(Fork::PrePoc2, 10)
+            .build();
+
+        assert_eq!(
+            MIN_FEE_NQT.get_flux_value(9, &historical_moments),
+            100_000_000
+        );
+        assert_eq!(MIN_FEE_NQT.get_flux_value(10, &historical_moments), 735_000);
+    }
+}
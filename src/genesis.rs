@@ -0,0 +1,126 @@
+//! The genesis block every chain starts from, and seeding the local chain with it.
+//!
+//! The genesis block is the only block that is built in rather than downloaded. It has no
+//! transactions, and its generator key and signatures are all zeroes.
+
+use sha2::{Digest, Sha256};
+
+use crate::{
+    models::{
+        datastore::{Datastore, DatastoreError},
+        p2p::{B1Block, BlockId},
+        Block,
+    },
+    network::Network,
+    poc::INITIAL_BASE_TARGET,
+};
+
+/// The ID of the Signum mainnet genesis block.
+pub const SIGNUM_GENESIS_BLOCK_ID: u64 = 3_444_294_670_862_540_038;
+
+/// Returns the genesis block of the network.
+///
+/// Devnets get a generation signature of their own, so their chains can't be mixed up with
+/// mainnet's even though they start from the same kind of block.
+pub fn genesis_block(network: Network) -> B1Block {
+    let generation_signature = match network {
+        Network::Mainnet => [0; 32],
+        Network::Devnet => Sha256::digest(network.network_name()).into(),
+    };
+
+    B1Block {
+        version: -1,
+        timestamp: 0,
+        previous_block: 0,
+        total_amount_nqt: 0,
+        total_fee_nqt: 0,
+        total_fee_cashback_nqt: 0,
+        total_fee_burnt_nqt: 0,
+        payload_length: 0,
+        payload_hash: hex::encode(Sha256::digest([])),
+        generator_public_key: hex::encode([0; 32]),
+        generation_signature: hex::encode(generation_signature),
+        previous_block_hash: None,
+        block_signature: hex::encode([0; 64]),
+        transactions: Vec::new(),
+        nonce: 0,
+        base_target: INITIAL_BASE_TARGET,
+        block_ats: None,
+    }
+}
+
+/// Returns the ID of the network's genesis block.
+pub fn genesis_block_id(network: Network) -> BlockId {
+    Block::try_from(genesis_block(network))
+        .expect("the built in genesis block is well formed")
+        .id
+}
+
+/// Stores the network's genesis block if the local chain is empty, or checks that the stored
+/// one is the network's if it isn't.
+///
+/// A different genesis block means the database belongs to another network, and the node
+/// must not start on it.
+#[tracing::instrument(skip(database))]
+pub async fn initialize_chain(
+    database: &Datastore,
+    network: Network,
+) -> Result<(), ChainInitializationError> {
+    let expected = genesis_block_id(network);
+
+    match database.get_block_id_at_height(0).await? {
+        Some(stored) if BlockId::from(stored) == expected => {
+            tracing::info!("Stored genesis block {} matches the network", stored);
+            Ok(())
+        }
+        Some(stored) => Err(ChainInitializationError::GenesisBlockMismatch {
+            expected,
+            actual: stored.into(),
+        }),
+        None => {
+            tracing::info!("Storing genesis block {}", expected);
+            // The genesis block adds no difficulty, as it isn't forged
+            database
+                .store_block(&genesis_block(network), expected.into(), 0, &0u8.into())
+                .await?;
+            Ok(())
+        }
+    }
+}
+
+/// Represents the reasons the local chain can't be initialized.
+#[derive(thiserror::Error)]
+pub enum ChainInitializationError {
+    #[error(
+        "the database holds genesis block {actual} instead of the network's {expected}, so it \
+         belongs to another network"
+    )]
+    GenesisBlockMismatch { expected: BlockId, actual: BlockId },
+    #[error(transparent)]
+    DatastoreError(#[from] DatastoreError),
+}
+
+impl std::fmt::Debug for ChainInitializationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        crate::error_chain_fmt(self, f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{models::p2p::BlockId, network::Network};
+
+    use super::{genesis_block_id, SIGNUM_GENESIS_BLOCK_ID};
+
+    #[test]
+    fn genesis_block_has_the_network_genesis_block_id() {
+        assert_eq!(
+            genesis_block_id(Network::Mainnet),
+            BlockId::from(SIGNUM_GENESIS_BLOCK_ID)
+        );
+        assert_ne!(
+            genesis_block_id(Network::Devnet),
+            BlockId::from(SIGNUM_GENESIS_BLOCK_ID)
+        );
+    }
+}
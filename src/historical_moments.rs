@@ -4,26 +4,186 @@
 
 use std::marker::PhantomData;
 
+use crate::configuration;
+
 pub trait HistoricalMomentsState {}
 
+#[derive(Clone, Debug)]
 pub struct Building;
 impl HistoricalMomentsState for Building {}
 
+#[derive(Clone, Debug)]
 pub struct Active;
 impl HistoricalMomentsState for Active {}
 
+/// A hard fork in the chain's history, in the order they happened on mainnet.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Fork {
+    Genesis,
+    RewardRecipientEnable,
+    DigitalGoodsStoreEnable,
+    AutomatedTransactionEnable,
+    AutomatedTransactionFix1,
+    AutomatedTransactionFix2,
+    AutomatedTransactionFix3,
+    PrePoc2,
+    Poc2Enable,
+    SodiumEnable,
+    SignumNameChange,
+    PocPlusEnable,
+    SpeedwayEnable,
+    SmartTokenEnable,
+    SmartFeesEnable,
+    SmartAtsEnable,
+    AutomatedTransactionFix4,
+    DistributionFixEnable,
+    PkFreeze,
+    PkFreeze2,
+    SmartAliasEnable,
+    NextFork,
+}
+
+impl Fork {
+    pub const ALL: [Fork; 22] = [
+        Fork::Genesis,
+        Fork::RewardRecipientEnable,
+        Fork::DigitalGoodsStoreEnable,
+        Fork::AutomatedTransactionEnable,
+        Fork::AutomatedTransactionFix1,
+        Fork::AutomatedTransactionFix2,
+        Fork::AutomatedTransactionFix3,
+        Fork::PrePoc2,
+        Fork::Poc2Enable,
+        Fork::SodiumEnable,
+        Fork::SignumNameChange,
+        Fork::PocPlusEnable,
+        Fork::SpeedwayEnable,
+        Fork::SmartTokenEnable,
+        Fork::SmartFeesEnable,
+        Fork::SmartAtsEnable,
+        Fork::AutomatedTransactionFix4,
+        Fork::DistributionFixEnable,
+        Fork::PkFreeze,
+        Fork::PkFreeze2,
+        Fork::SmartAliasEnable,
+        Fork::NextFork,
+    ];
+
+    /// The height at which the fork happened on mainnet.
+    pub fn mainnet_height(&self) -> u32 {
+        match self {
+            Fork::Genesis => 0,
+            Fork::RewardRecipientEnable => 6_500,
+            Fork::DigitalGoodsStoreEnable => 11_800,
+            Fork::AutomatedTransactionEnable => 49_200,
+            Fork::AutomatedTransactionFix1 => 67_000,
+            Fork::AutomatedTransactionFix2 => 92_000,
+            Fork::AutomatedTransactionFix3 => 255_000,
+            Fork::PrePoc2 => 500_000,
+            Fork::Poc2Enable => 502_000,
+            Fork::SodiumEnable => 765_000,
+            Fork::SignumNameChange => 875_500,
+            Fork::PocPlusEnable => 878_000,
+            Fork::SpeedwayEnable => 941_100,
+            Fork::SmartTokenEnable => 1_029_000,
+            Fork::SmartFeesEnable => 1_029_000,
+            Fork::SmartAtsEnable => 1_029_000,
+            Fork::AutomatedTransactionFix4 => 1_051_900,
+            Fork::DistributionFixEnable => 1_051_900,
+            Fork::PkFreeze => 1_099_400,
+            Fork::PkFreeze2 => 1_150_000,
+            Fork::SmartAliasEnable => 1_150_000,
+            Fork::NextFork => u32::MAX,
+        }
+    }
+}
+
+/// The fork heights of the chain. Built as [`HistoricalMoments<Building>`], which starts
+/// from the mainnet heights and takes overrides, then frozen into
+/// [`HistoricalMoments<Active>`], which is only ever read. Build it once at startup and
+/// share it behind an `Arc`.
+#[derive(Clone, Debug)]
 pub struct HistoricalMoments<S: HistoricalMomentsState> {
+    heights: [u32; Fork::ALL.len()],
     _phantom: PhantomData<S>,
 }
 
 impl HistoricalMoments<Building> {
     pub fn new() -> HistoricalMoments<Building> {
         HistoricalMoments::<Building> {
+            heights: Fork::ALL.map(|f| f.mainnet_height()),
             _phantom: PhantomData,
         }
     }
-    pub fn build(&self) -> HistoricalMoments<Active> {
+
+    /// Moves a single fork to a different height.
+    pub fn with_height(mut self, fork: Fork, height: u32) -> Self {
+        self.heights[fork as usize] = height;
+        self
+    }
+
+    /// Applies every override set in the `historical_moments` configuration section.
+    pub fn with_overrides(self, overrides: &configuration::HistoricalMoments) -> Self {
+        let configuration::HistoricalMoments {
+            reward_recipient_enable,
+            digital_goods_store_enable,
+            automated_transaction_enable,
+            automated_transaction_fix_1,
+            automated_transaction_fix_2,
+            automated_transaction_fix_3,
+            pre_poc2,
+            poc2_enable,
+            sodium_enable,
+            signum_name_change,
+            poc_plus_enable,
+            speedway_enable,
+            smart_token_enable,
+            smart_fees_enable,
+            smart_ats_enable,
+            automated_transaction_fix_4,
+            distribution_fix_enable,
+            pk_freeze,
+            pk_freeze_2,
+            smart_alias_enable,
+            next_fork,
+        } = *overrides;
+
+        [
+            (Fork::RewardRecipientEnable, reward_recipient_enable),
+            (Fork::DigitalGoodsStoreEnable, digital_goods_store_enable),
+            (
+                Fork::AutomatedTransactionEnable,
+                automated_transaction_enable,
+            ),
+            (Fork::AutomatedTransactionFix1, automated_transaction_fix_1),
+            (Fork::AutomatedTransactionFix2, automated_transaction_fix_2),
+            (Fork::AutomatedTransactionFix3, automated_transaction_fix_3),
+            (Fork::PrePoc2, pre_poc2),
+            (Fork::Poc2Enable, poc2_enable),
+            (Fork::SodiumEnable, sodium_enable),
+            (Fork::SignumNameChange, signum_name_change),
+            (Fork::PocPlusEnable, poc_plus_enable),
+            (Fork::SpeedwayEnable, speedway_enable),
+            (Fork::SmartTokenEnable, smart_token_enable),
+            (Fork::SmartFeesEnable, smart_fees_enable),
+            (Fork::SmartAtsEnable, smart_ats_enable),
+            (Fork::AutomatedTransactionFix4, automated_transaction_fix_4),
+            (Fork::DistributionFixEnable, distribution_fix_enable),
+            (Fork::PkFreeze, pk_freeze),
+            (Fork::PkFreeze2, pk_freeze_2),
+            (Fork::SmartAliasEnable, smart_alias_enable),
+            (Fork::NextFork, next_fork),
+        ]
+        .into_iter()
+        .fold(self, |moments, (fork, height)| match height {
+            Some(height) => moments.with_height(fork, height),
+            None => moments,
+        })
+    }
+
+    pub fn build(self) -> HistoricalMoments<Active> {
         HistoricalMoments::<Active> {
+            heights: self.heights,
             _phantom: PhantomData,
         }
     }
@@ -36,117 +196,192 @@ impl Default for HistoricalMoments<Building> {
 }
 
 impl HistoricalMoments<Active> {
+    /// The height at which the given fork happened.
+    pub fn height_of(&self, fork: Fork) -> u32 {
+        self.heights[fork as usize]
+    }
+
+    /// Returns whether the rules of the given fork apply to the block at `height`.
+    pub fn is_active(&self, fork: Fork, height: u64) -> bool {
+        height >= u64::from(self.height_of(fork))
+    }
+
+    /// Returns the forks whose rules apply to the block at `height`.
+    pub fn active_forks(&self, height: u64) -> Vec<Fork> {
+        Fork::ALL
+            .into_iter()
+            .filter(|f| self.is_active(*f, height))
+            .collect()
+    }
+
     /// The height at which the genesis block was created. Always 0.
     pub fn genesis(&self) -> u32 {
-        todo!()
+        self.height_of(Fork::Genesis)
     }
 
     /// The height at which the reward recipient feature was enabled.
     pub fn reward_recipient_enable(&self) -> u32 {
-        todo!()
+        self.height_of(Fork::RewardRecipientEnable)
     }
 
     /// The height at which the digital goods store was enabled.
     pub fn digital_goods_store_enable(&self) -> u32 {
-        todo!()
+        self.height_of(Fork::DigitalGoodsStoreEnable)
     }
 
     /// The height at which the automated transactions feature was enabled.
     pub fn automated_transaction_enable(&self) -> u32 {
-        todo!()
+        self.height_of(Fork::AutomatedTransactionEnable)
     }
 
     /// The height at which the automated transactions feature was fixed the first time.
     //TODO: Try to find a description of the fix
     pub fn automated_transaction_fix_1(&self) -> u32 {
-        todo!()
+        self.height_of(Fork::AutomatedTransactionFix1)
     }
 
     /// The height at which the automated transactions feature was fixed the second time.
     //TODO: Try to find a description of the fix
     pub fn automated_transaction_fix_2(&self) -> u32 {
-        todo!()
+        self.height_of(Fork::AutomatedTransactionFix2)
     }
 
     /// The height at which the automated transactions feature was fixed the third time.
     //TODO: Try to find a description of the fix
     pub fn automated_transaction_fix_3(&self) -> u32 {
-        todo!()
+        self.height_of(Fork::AutomatedTransactionFix3)
     }
 
     /// The height at which the pre-PoC2 format prepration was enabled.
     pub fn pre_poc2(&self) -> u32 {
-        todo!()
+        self.height_of(Fork::PrePoc2)
     }
 
     /// The height at which the PoC2 format was fully enabled.
     pub fn poc2_enable(&self) -> u32 {
-        todo!()
+        self.height_of(Fork::Poc2Enable)
     }
 
     /// The height at which the sodium feature was enabled.
     pub fn sodium_enable(&self) -> u32 {
-        todo!()
+        self.height_of(Fork::SodiumEnable)
     }
 
     /// The height at which the project was renamed from BurstCoin to Signum.
     pub fn signum_name_change(&self) -> u32 {
-        todo!()
+        self.height_of(Fork::SignumNameChange)
     }
 
     /// The height at which the PoC+ format was enabled.
     pub fn poc_plus_enable(&self) -> u32 {
-        todo!()
+        self.height_of(Fork::PocPlusEnable)
     }
 
     /// The height at which the speedway feature was enabled.
     pub fn speedway_enable(&self) -> u32 {
-        todo!()
+        self.height_of(Fork::SpeedwayEnable)
     }
 
     /// The height at which the smart token feature was enabled.
     pub fn smart_token_enable(&self) -> u32 {
-        todo!()
+        self.height_of(Fork::SmartTokenEnable)
     }
 
     /// The height at which the smart fees feature was enabled.
     pub fn smart_fees_enable(&self) -> u32 {
-        todo!()
+        self.height_of(Fork::SmartFeesEnable)
     }
 
     /// The height at which the smart automated transactions feature was enabled.
     pub fn smart_ats_enable(&self) -> u32 {
-        todo!()
+        self.height_of(Fork::SmartAtsEnable)
     }
 
     /// The height at which the automated transactions feature was fixed the fourth time.
     //TODO: Try to find a description of the fix
     pub fn automated_transaction_fix_4(&self) -> u32 {
-        todo!()
+        self.height_of(Fork::AutomatedTransactionFix4)
     }
 
     /// The height at whcih the distribution fix was enabled.
     pub fn distribution_fix_enable(&self) -> u32 {
-        todo!()
+        self.height_of(Fork::DistributionFixEnable)
     }
 
     /// The height at which the first public key freeze was enacted.
     pub fn pk_freeze(&self) -> u32 {
-        todo!()
+        self.height_of(Fork::PkFreeze)
     }
 
     /// The height at which the second public key freeze was enacted.
     pub fn pk_freeze_2(&self) -> u32 {
-        todo!()
+        self.height_of(Fork::PkFreeze2)
     }
 
     /// The height at which the smart alias feature was enabled.
     pub fn smart_alias_enable(&self) -> u32 {
-        todo!()
+        self.height_of(Fork::SmartAliasEnable)
     }
 
     /// The next fork height is always u32::MAX to ensure the flux values alwasy have a value.
     pub fn next_fork(&self) -> u32 {
-        todo!()
+        self.height_of(Fork::NextFork)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::configuration;
+
+    use super::{Fork, HistoricalMoments};
+
+    #[test]
+    fn build_defaults_to_mainnet_heights() {
+        let historical_moments = HistoricalMoments::new().build();
+
+        assert_eq!(historical_moments.genesis(), 0);
+        assert_eq!(historical_moments.poc2_enable(), 502_000);
+        assert_eq!(historical_moments.smart_alias_enable(), 1_150_000);
+        assert_eq!(historical_moments.next_fork(), u32::MAX);
+    }
+
+    #[test]
+    fn fork_all_is_in_declaration_order() {
+        // Heights are looked up by `fork as usize`, so each fork must sit at its own index
+        for (index, fork) in Fork::ALL.into_iter().enumerate() {
+            assert_eq!(fork as usize, index, "{:?} is out of order", fork);
+        }
+    }
+
+    #[test]
+    fn with_overrides_only_replaces_configured_heights() {
+        let overrides = configuration::HistoricalMoments {
+            poc2_enable: Some(10),
+            sodium_enable: Some(20),
+            ..Default::default()
+        };
+
+        let historical_moments = HistoricalMoments::new().with_overrides(&overrides).build();
+
+        assert_eq!(historical_moments.poc2_enable(), 10);
+        assert_eq!(historical_moments.sodium_enable(), 20);
+        assert_eq!(historical_moments.pre_poc2(), 500_000);
+    }
+
+    #[test]
+    fn active_forks_lists_forks_at_or_below_height() {
+        let historical_moments = HistoricalMoments::new().build();
+
+        assert_eq!(historical_moments.active_forks(0), vec![Fork::Genesis]);
+        assert_eq!(
+            historical_moments.active_forks(11_800),
+            vec![
+                Fork::Genesis,
+                Fork::RewardRecipientEnable,
+                Fork::DigitalGoodsStoreEnable
+            ]
+        );
+        assert!(!historical_moments.is_active(Fork::NextFork, u64::from(u32::MAX) - 1));
+        assert!(historical_moments.is_active(Fork::PocPlusEnable, 878_000));
     }
 }
@@ -2,11 +2,16 @@ use actix_web::HttpResponse;
 use itertools::Itertools;
 
 pub mod configuration;
+pub mod crypto;
 pub mod flux_capacitor;
+pub mod genesis;
 pub mod models;
+pub mod network;
 pub mod peers;
+pub mod poc;
 pub mod srs_api;
 pub mod telemetry;
+pub mod validation;
 pub mod workers;
 
 mod historical_moments;
@@ -25,6 +30,19 @@ pub fn error_chain_fmt(
     Ok(())
 }
 
+/// The start of the Signum epoch, 2014-08-11 02:00:00 UTC, as seconds since the Unix epoch.
+/// Block and transaction timestamps are seconds since this moment.
+pub const SIGNUM_EPOCH: u64 = 1_407_722_400;
+
+/// Returns the current time as seconds since the [`SIGNUM_EPOCH`].
+pub fn signum_timestamp() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("system time is before the unix epoch")
+        .as_secs()
+        .saturating_sub(SIGNUM_EPOCH)
+}
+
 #[tracing::instrument(skip_all)]
 pub async fn health_check() -> HttpResponse {
     HttpResponse::Ok().finish()
@@ -1,14 +1,19 @@
-use std::fmt::{Debug, Display};
+use std::{
+    fmt::{Debug, Display},
+    sync::Arc,
+};
 
 use anyhow::Result;
 
 use signum_node_rs::{
     configuration::get_configuration,
+    genesis::initialize_chain,
     srs_api::SrsApiApplication,
     telemetry::{get_subscriber, init_subscriber},
     workers::{
         block_downloader::run_block_downloader_forever, peer_finder::run_peer_finder_forever,
         peer_info_trader::run_peer_info_trader_forever,
+        unconfirmed_transaction_puller::run_unconfirmed_transaction_puller_forever,
     },
 };
 use tokio::task::JoinError;
@@ -32,22 +37,37 @@ async fn start() -> Result<()> {
     //let configuration = CONFIGURATION;
 
     let database = configuration.database.get_db().await?;
+    initialize_chain(&database, configuration.node.network).await?;
+
+    // The fork heights are fixed for the life of the node, so every task shares one copy
+    let historical_moments = Arc::new(configuration.historical_moments());
 
     // Create the Block Downloader task
     let block_downloader_task = tokio::spawn(run_block_downloader_forever(
         database.clone(),
         configuration.clone(),
+        historical_moments.clone(),
     ));
 
     // Create the p2p api webserver task
-    let p2p_api = SrsApiApplication::build(configuration.clone(), database.clone()).await?;
+    let p2p_api = SrsApiApplication::build(
+        configuration.clone(),
+        database.clone(),
+        historical_moments.clone(),
+    )
+    .await?;
     let p2p_api_task = tokio::spawn(p2p_api.run_until_stopped());
 
     // Create the peer finder task
     let peer_finder_task = tokio::spawn(run_peer_finder_forever(database.clone(), configuration));
 
     // Create the peer info trader task
-    let peer_info_trader_task = tokio::spawn(run_peer_info_trader_forever(database));
+    let peer_info_trader_task = tokio::spawn(run_peer_info_trader_forever(database.clone()));
+
+    // Create the unconfirmed transaction puller task
+    let unconfirmed_transaction_puller_task = tokio::spawn(
+        run_unconfirmed_transaction_puller_forever(database, historical_moments),
+    );
 
     // Select on all the tasks to report closure status
     tokio::select! {
@@ -55,6 +75,7 @@ async fn start() -> Result<()> {
         o = p2p_api_task => report_exit("P2P API Server", o),
         o = peer_finder_task => report_exit("Peer Finder", o),
         o = peer_info_trader_task => report_exit("Peer Info Trader", o),
+        o = unconfirmed_transaction_puller_task => report_exit("Unconfirmed Transaction Puller", o),
     };
 
     Ok(())
@@ -1,5 +1,9 @@
 pub mod datastore;
 pub mod p2p;
 
+mod account_id;
+mod amount;
 mod block;
-pub use block::Block;
+pub use account_id::{AccountId, AccountIdParseError};
+pub use amount::Amount;
+pub use block::{Block, BlockConversionError};
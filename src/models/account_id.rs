@@ -0,0 +1,259 @@
+use std::{fmt::Display, num::ParseIntError, str::FromStr};
+
+use serde_with::{DeserializeFromStr, SerializeDisplay};
+use sha2::{Digest, Sha256};
+
+/// An account's ID: the first 8 bytes of the SHA-256 hash of its public key, read as a
+/// little-endian number.
+///
+/// Parses from either the plain number or the Reed-Solomon address (`S-XXXX-XXXX-XXXX-XXXXX`),
+/// and displays as the plain number. Use [`AccountId::to_rs_address`] for the address.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    DeserializeFromStr,
+    Eq,
+    Hash,
+    Ord,
+    PartialEq,
+    PartialOrd,
+    SerializeDisplay,
+)]
+pub struct AccountId(u64);
+
+impl AccountId {
+    /// Derives the ID of the account with the given public key.
+    pub fn from_public_key(public_key: &[u8; 32]) -> Self {
+        let hash = Sha256::digest(public_key);
+        let mut id = [0u8; 8];
+        id.copy_from_slice(&hash[..8]);
+        Self(u64::from_le_bytes(id))
+    }
+
+    /// Returns the account's Reed-Solomon address, such as `S-C9TD-24WW-RUD3-FGHVJ`.
+    pub fn to_rs_address(&self) -> String {
+        format!("{}-{}", RS_PREFIX, reed_solomon::encode(self.0))
+    }
+}
+
+impl From<u64> for AccountId {
+    fn from(value: u64) -> Self {
+        Self(value)
+    }
+}
+
+impl From<AccountId> for u64 {
+    fn from(value: AccountId) -> Self {
+        value.0
+    }
+}
+
+impl Display for AccountId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for AccountId {
+    type Err = AccountIdParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        match s.split_once('-') {
+            Some((prefix, address))
+                if [RS_PREFIX, LEGACY_RS_PREFIX]
+                    .iter()
+                    .any(|p| p.eq_ignore_ascii_case(prefix)) =>
+            {
+                Ok(Self(reed_solomon::decode(address)?))
+            }
+            _ => Ok(Self(u64::from_str(s)?)),
+        }
+    }
+}
+
+/// The prefix of Reed-Solomon addresses.
+const RS_PREFIX: &str = "S";
+/// The prefix addresses had before the network was renamed to Signum, still accepted by BRS.
+const LEGACY_RS_PREFIX: &str = "BURST";
+
+/// Represents the reasons a string isn't a valid [`AccountId`].
+#[derive(thiserror::Error)]
+pub enum AccountIdParseError {
+    #[error("account ID is not a number or an address")]
+    InvalidNumber(#[from] ParseIntError),
+    #[error("address contains the invalid character {0:?}")]
+    InvalidCharacter(char),
+    #[error("address should have 17 characters but had {0}")]
+    InvalidLength(usize),
+    #[error("address contains a typo")]
+    InvalidChecksum,
+    #[error("address is too large to be an account ID")]
+    OutOfRange,
+}
+
+impl std::fmt::Debug for AccountIdParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        crate::error_chain_fmt(self, f)
+    }
+}
+
+/// The Reed-Solomon code over GF(32) BRS uses for addresses: 13 base 32 digits of the ID
+/// followed by 4 parity digits, shuffled into the order they're written in.
+mod reed_solomon {
+    use super::AccountIdParseError;
+
+    const ALPHABET: &[u8; 32] = b"23456789ABCDEFGHJKLMNPQRSTUVWXYZ";
+    const DATA_LENGTH: usize = 13;
+    const CODEWORD_LENGTH: usize = 17;
+    /// The codeword position of each written character.
+    const CODEWORD_MAP: [usize; CODEWORD_LENGTH] =
+        [3, 2, 1, 0, 7, 6, 5, 4, 13, 14, 15, 16, 12, 8, 9, 10, 11];
+    const GEXP: [u8; 32] = [
+        1, 2, 4, 8, 16, 5, 10, 20, 13, 26, 17, 7, 14, 28, 29, 31, 27, 19, 3, 6, 12, 24, 21, 15, 30,
+        25, 23, 11, 22, 9, 18, 1,
+    ];
+    const GLOG: [u8; 32] = [
+        0, 0, 1, 18, 2, 5, 19, 11, 3, 29, 6, 27, 20, 8, 12, 23, 4, 10, 30, 17, 7, 22, 28, 26, 21,
+        25, 9, 16, 13, 14, 24, 15,
+    ];
+
+    pub(super) fn encode(id: u64) -> String {
+        let mut codeword = [0u8; CODEWORD_LENGTH];
+        let mut remaining = id;
+        for digit in codeword.iter_mut().take(DATA_LENGTH) {
+            *digit = (remaining % 32) as u8;
+            remaining /= 32;
+        }
+
+        let mut parity = [0u8; 4];
+        for i in (0..DATA_LENGTH).rev() {
+            let feedback = codeword[i] ^ parity[3];
+            parity[3] = parity[2] ^ gmult(30, feedback);
+            parity[2] = parity[1] ^ gmult(6, feedback);
+            parity[1] = parity[0] ^ gmult(9, feedback);
+            parity[0] = gmult(17, feedback);
+        }
+        codeword[DATA_LENGTH..].copy_from_slice(&parity);
+
+        let mut address = String::with_capacity(CODEWORD_LENGTH + 3);
+        for (i, position) in CODEWORD_MAP.iter().enumerate() {
+            address.push(ALPHABET[usize::from(codeword[*position])] as char);
+            if i % 4 == 3 && i < DATA_LENGTH {
+                address.push('-');
+            }
+        }
+        address
+    }
+
+    pub(super) fn decode(address: &str) -> Result<u64, AccountIdParseError> {
+        let mut codeword = [0u8; CODEWORD_LENGTH];
+        let mut length = 0;
+        for c in address.chars().filter(|c| *c != '-') {
+            let digit = ALPHABET
+                .iter()
+                .position(|a| char::from(*a) == c.to_ascii_uppercase())
+                .ok_or(AccountIdParseError::InvalidCharacter(c))?;
+            if let Some(position) = CODEWORD_MAP.get(length) {
+                codeword[*position] = digit as u8;
+            }
+            length += 1;
+        }
+        if length != CODEWORD_LENGTH {
+            return Err(AccountIdParseError::InvalidLength(length));
+        }
+        if !is_codeword_valid(&codeword) {
+            return Err(AccountIdParseError::InvalidChecksum);
+        }
+
+        let id = codeword[..DATA_LENGTH]
+            .iter()
+            .rev()
+            .fold(0u128, |id, digit| id * 32 + u128::from(*digit));
+        u64::try_from(id).map_err(|_| AccountIdParseError::OutOfRange)
+    }
+
+    /// Returns whether all syndromes of the codeword are zero, meaning no errors were detected.
+    fn is_codeword_valid(codeword: &[u8; CODEWORD_LENGTH]) -> bool {
+        (1..5).all(|i| {
+            (0..31)
+                .filter(|j| !(13..27).contains(j))
+                .fold(0, |syndrome, j| {
+                    let position = if j > 26 { j - 14 } else { j };
+                    syndrome ^ gmult(codeword[position], GEXP[(i * j) % 31])
+                })
+                == 0
+        })
+    }
+
+    fn gmult(a: u8, b: u8) -> u8 {
+        if a == 0 || b == 0 {
+            return 0;
+        }
+        GEXP[(usize::from(GLOG[usize::from(a)]) + usize::from(GLOG[usize::from(b)])) % 31]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::{AccountId, AccountIdParseError};
+
+    #[test]
+    fn rs_address_round_trips_mainnet_accounts() {
+        for (id, address) in [
+            (15_595_382_771_538_009_899, "S-C9TD-24WW-RUD3-FGHVJ"),
+            (13_657_951_110_994_294_056, "S-GXBA-7JP9-NR7S-DCQ4V"),
+        ] {
+            assert_eq!(AccountId::from(id).to_rs_address(), address);
+            assert_eq!(AccountId::from_str(address).unwrap(), AccountId::from(id));
+        }
+        assert_eq!(
+            AccountId::from_str("burst-c9td-24ww-rud3-fghvj").unwrap(),
+            AccountId::from(15_595_382_771_538_009_899)
+        );
+        assert_eq!(AccountId::from(0).to_rs_address(), "S-2222-2222-2222-22222");
+    }
+
+    #[test]
+    fn from_str_parses_numeric_ids() {
+        assert_eq!(
+            AccountId::from_str("15595382771538009899").unwrap(),
+            AccountId::from(15_595_382_771_538_009_899)
+        );
+        assert!(matches!(
+            AccountId::from_str("SNRADDRESS"),
+            Err(AccountIdParseError::InvalidNumber(_))
+        ));
+    }
+
+    #[test]
+    fn from_str_detects_typos_in_addresses() {
+        assert!(matches!(
+            AccountId::from_str("S-C9TD-24WW-RUD3-FGHVK"),
+            Err(AccountIdParseError::InvalidChecksum)
+        ));
+        assert!(matches!(
+            AccountId::from_str("S-C9TD-24WW-RUD3-FGHV"),
+            Err(AccountIdParseError::InvalidLength(16))
+        ));
+        assert!(matches!(
+            AccountId::from_str("S-C9TD-24WW-RUD3-FGHV1"),
+            Err(AccountIdParseError::InvalidCharacter('1'))
+        ));
+    }
+
+    #[test]
+    fn from_public_key_matches_mainnet_account() {
+        let public_key =
+            hex::decode("7347ae2ec9aaa0a93f90a1c394c370a629227928ba2f24843634c81358d2b403")
+                .unwrap();
+
+        let id = AccountId::from_public_key(&public_key.try_into().unwrap());
+
+        assert_eq!(id, AccountId::from(15_595_382_771_538_009_899));
+    }
+}
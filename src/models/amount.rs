@@ -0,0 +1,42 @@
+use std::{fmt::Display, iter::Sum};
+
+/// The number of NQT in one Signa.
+pub(crate) const NQT_PER_SIGNA: u64 = 100_000_000;
+
+/// An amount of Signa, held as a whole number of NQT (one hundred-millionth of a Signa).
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Amount(u64);
+
+impl Amount {
+    pub const ZERO: Self = Self(0);
+
+    pub const fn from_nqt(nqt: u64) -> Self {
+        Self(nqt)
+    }
+
+    pub const fn nqt(self) -> u64 {
+        self.0
+    }
+
+    /// Returns the number of whole Signa in the amount, dropping any fraction.
+    pub const fn whole_signa(self) -> u64 {
+        self.0 / NQT_PER_SIGNA
+    }
+}
+
+impl Display for Amount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}.{:08} SIGNA",
+            self.0 / NQT_PER_SIGNA,
+            self.0 % NQT_PER_SIGNA
+        )
+    }
+}
+
+impl Sum for Amount {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        Self(iter.map(|a| a.0).sum())
+    }
+}
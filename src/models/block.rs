@@ -1,18 +1,391 @@
-#[derive(Debug)]
-pub struct Block;
+use sha2::{Digest, Sha256};
 
-impl From<ExchangeableBlock> for Block {
-    fn from(value: ExchangeableBlock) -> Self {
-        todo!()
+use crate::crypto;
+
+use super::{
+    p2p::{B1Block, BlockId, Transaction, TransactionConversionError},
+    Amount,
+};
+
+/// A block with its fields decoded from the hex strings used on the wire.
+#[derive(Clone, Debug)]
+pub struct Block {
+    /// The block's height, which isn't part of the exchanged block and is only known once the
+    /// block has been placed in a chain.
+    pub height: Option<u64>,
+    pub id: BlockId,
+    /// The genesis block is version -1.
+    pub version: i32,
+    pub timestamp: u64,
+    pub previous_block_id: BlockId,
+    pub previous_block_hash: Option<[u8; 32]>,
+    pub total_amount: Amount,
+    pub total_fee: Amount,
+    pub total_fee_cashback: Amount,
+    pub total_fee_burnt: Amount,
+    pub payload_length: u32,
+    pub payload_hash: [u8; 32],
+    pub generator_public_key: [u8; 32],
+    pub generation_signature: [u8; 32],
+    pub block_signature: [u8; 64],
+    pub nonce: u64,
+    pub base_target: u64,
+    pub block_ats: Option<Vec<u8>>,
+    pub transactions: Vec<Transaction>,
+}
+
+impl TryFrom<B1Block> for Block {
+    type Error = BlockConversionError;
+
+    fn try_from(value: B1Block) -> Result<Self, Self::Error> {
+        let previous_block_hash = match &value.previous_block_hash {
+            Some(hash) => Some(decode_fixed("previousBlockHash", hash)?),
+            None if value.version > 1 => {
+                return Err(BlockConversionError::MissingPreviousBlockHash)
+            }
+            None => None,
+        };
+        let block_ats = value
+            .block_ats
+            .as_ref()
+            .map(|ats| {
+                hex::decode(ats).map_err(|source| BlockConversionError::InvalidHex {
+                    field: "blockATs",
+                    source,
+                })
+            })
+            .transpose()?;
+        let transactions = value
+            .transactions
+            .into_iter()
+            .map(Transaction::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut block = Self {
+            height: None,
+            id: BlockId::default(),
+            payload_hash: decode_fixed("payloadHash", &value.payload_hash)?,
+            generator_public_key: decode_fixed("generatorPublicKey", &value.generator_public_key)?,
+            generation_signature: decode_fixed("generationSignature", &value.generation_signature)?,
+            block_signature: decode_fixed("blockSignature", &value.block_signature)?,
+            version: value.version,
+            timestamp: value.timestamp,
+            previous_block_id: value.previous_block.into(),
+            previous_block_hash,
+            total_amount: Amount::from_nqt(value.total_amount_nqt),
+            total_fee: Amount::from_nqt(value.total_fee_nqt),
+            total_fee_cashback: Amount::from_nqt(value.total_fee_cashback_nqt),
+            total_fee_burnt: Amount::from_nqt(value.total_fee_burnt_nqt),
+            payload_length: value.payload_length,
+            nonce: value.nonce,
+            base_target: value.base_target,
+            block_ats,
+            transactions,
+        };
+        block.id = BlockId::from_full_hash(&block.full_hash());
+
+        Ok(block)
+    }
+}
+
+impl Block {
+    /// Returns the block in the binary layout BRS uses for hashing and signing.
+    ///
+    /// Blocks before version 3 hold their totals in whole Signa, and only blocks after version
+    /// 1 carry the previous block's hash. BRS always sizes its buffer for that hash, so older
+    /// blocks end with 32 zero bytes in its place.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(232 + self.block_ats.as_ref().map_or(0, Vec::len));
+        bytes.extend_from_slice(&self.version.to_le_bytes());
+        bytes.extend_from_slice(&(self.timestamp as u32).to_le_bytes());
+        bytes.extend_from_slice(&u64::from(self.previous_block_id).to_le_bytes());
+        bytes.extend_from_slice(&(self.transactions.len() as u32).to_le_bytes());
+        if self.version < 3 {
+            bytes.extend_from_slice(&(self.total_amount.whole_signa() as u32).to_le_bytes());
+            bytes.extend_from_slice(&(self.total_fee.whole_signa() as u32).to_le_bytes());
+        } else {
+            bytes.extend_from_slice(&self.total_amount.nqt().to_le_bytes());
+            bytes.extend_from_slice(&self.total_fee.nqt().to_le_bytes());
+        }
+        bytes.extend_from_slice(&self.payload_length.to_le_bytes());
+        bytes.extend_from_slice(&self.payload_hash);
+        bytes.extend_from_slice(&self.generator_public_key);
+        bytes.extend_from_slice(&self.generation_signature);
+        if self.version > 1 {
+            bytes.extend_from_slice(&self.previous_block_hash.unwrap_or_default());
+        }
+        bytes.extend_from_slice(&self.nonce.to_le_bytes());
+        if let Some(block_ats) = &self.block_ats {
+            bytes.extend_from_slice(block_ats);
+        }
+        bytes.extend_from_slice(&self.block_signature);
+        if self.version <= 1 {
+            bytes.extend_from_slice(&[0u8; 32]);
+        }
+
+        bytes
+    }
+
+    /// Returns the bytes the generator signs, which are the block's bytes without the last 64.
+    ///
+    /// Like BRS, this cuts from the end of the buffer, so for blocks before version 2 it also
+    /// cuts into the signature rather than the padding alone.
+    pub fn unsigned_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.to_bytes();
+        bytes.truncate(bytes.len() - self.block_signature.len());
+        bytes
+    }
+
+    /// Returns the SHA-256 hash of the block's bytes.
+    pub fn full_hash(&self) -> [u8; 32] {
+        Sha256::digest(self.to_bytes()).into()
     }
+
+    /// Returns whether the block is signed with the given public key.
+    ///
+    /// That's the generator's key, unless it assigned its rewards to another account such as a
+    /// pool, which then signs its blocks. Like BRS, only blocks from version 3 on need a
+    /// canonical signature. The genesis block isn't signed, so this is always false for it.
+    pub fn verify_signature(&self, public_key: &[u8; 32]) -> bool {
+        crypto::verify(
+            &self.block_signature,
+            &self.unsigned_bytes(),
+            public_key,
+            self.version >= 3,
+        )
+    }
+}
+
+/// Decodes a hex string that must hold exactly `N` bytes.
+fn decode_fixed<const N: usize>(
+    field: &'static str,
+    value: &str,
+) -> Result<[u8; N], BlockConversionError> {
+    let bytes =
+        hex::decode(value).map_err(|source| BlockConversionError::InvalidHex { field, source })?;
+    bytes
+        .try_into()
+        .map_err(|bytes: Vec<u8>| BlockConversionError::InvalidLength {
+            field,
+            expected: N,
+            actual: bytes.len(),
+        })
 }
 
-/// Maybe not needed at all
-#[derive(Debug)]
-pub struct ExchangeableBlock;
+/// Represents the reasons a [`B1Block`] can't be converted into a [`Block`].
+#[derive(thiserror::Error)]
+pub enum BlockConversionError {
+    #[error("{field} is not valid hex")]
+    InvalidHex {
+        field: &'static str,
+        #[source]
+        source: hex::FromHexError,
+    },
+    #[error("{field} should be {expected} bytes but was {actual}")]
+    InvalidLength {
+        field: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("previousBlockHash is required for blocks after version 1")]
+    MissingPreviousBlockHash,
+    #[error("block has a malformed transaction")]
+    MalformedTransaction(#[from] TransactionConversionError),
+}
+
+impl std::fmt::Debug for BlockConversionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        crate::error_chain_fmt(self, f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use sha2::{Digest, Sha256};
+
+    use crate::{
+        crypto,
+        models::{
+            p2p::{B1Block, BlockId},
+            Amount,
+        },
+    };
+
+    use super::{Block, BlockConversionError};
+
+    fn b1_block() -> B1Block {
+        serde_json::from_str(include_str!("../../tests/fixtures/b1_block.json")).unwrap()
+    }
+
+    #[test]
+    fn block_try_from_b1_block_decodes_fields() {
+        let b1_block = b1_block();
+
+        let block = Block::try_from(b1_block.clone()).unwrap();
+
+        assert_eq!(block.id, BlockId::from_full_hash(&block.full_hash()));
+        assert_eq!(
+            block.previous_block_id,
+            BlockId::from(b1_block.previous_block)
+        );
+        assert_eq!(block.height, None);
+        assert_eq!(hex::encode(block.payload_hash), b1_block.payload_hash);
+        assert_eq!(
+            hex::encode(block.generator_public_key),
+            b1_block.generator_public_key
+        );
+        assert_eq!(hex::encode(block.block_signature), b1_block.block_signature);
+        assert_eq!(
+            block.total_amount,
+            Amount::from_nqt(b1_block.total_amount_nqt)
+        );
+        assert_eq!(block.transactions.len(), b1_block.transactions.len());
+    }
+
+    #[test]
+    fn block_try_from_b1_block_reports_malformed_field() {
+        let mut b1_block = b1_block();
+        b1_block.generation_signature = "zz".to_string();
+
+        let result = Block::try_from(b1_block);
+        assert!(matches!(
+            result,
+            Err(BlockConversionError::InvalidHex {
+                field: "generationSignature",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn block_try_from_b1_block_reports_wrong_length() {
+        let mut b1_block = b1_block();
+        b1_block.block_signature = "00".repeat(32);
+
+        let result = Block::try_from(b1_block);
+        assert!(matches!(
+            result,
+            Err(BlockConversionError::InvalidLength {
+                field: "blockSignature",
+                expected: 64,
+                actual: 32,
+            })
+        ));
+    }
+
+    #[test]
+    fn block_try_from_b1_block_requires_previous_block_hash() {
+        let mut b1_block = b1_block();
+        b1_block.previous_block_hash = None;
+
+        let result = Block::try_from(b1_block);
+        assert!(matches!(
+            result,
+            Err(BlockConversionError::MissingPreviousBlockHash)
+        ));
+    }
+
+    #[test]
+    fn block_try_from_b1_block_reports_malformed_transaction() {
+        let mut b1_block = b1_block();
+        b1_block.transactions[0].signature = "zz".to_string();
+
+        let result = Block::try_from(b1_block);
+        assert!(matches!(
+            result,
+            Err(BlockConversionError::MalformedTransaction(_))
+        ));
+    }
+
+    /// The mainnet genesis block, which has no transactions and zeroed keys and signatures.
+    fn genesis_block() -> Block {
+        Block {
+            height: Some(0),
+            id: BlockId::default(),
+            version: -1,
+            timestamp: 0,
+            previous_block_id: BlockId::default(),
+            previous_block_hash: None,
+            total_amount: Amount::ZERO,
+            total_fee: Amount::ZERO,
+            total_fee_cashback: Amount::ZERO,
+            total_fee_burnt: Amount::ZERO,
+            payload_length: 0,
+            payload_hash: Sha256::digest([]).into(),
+            generator_public_key: [0; 32],
+            generation_signature: [0; 32],
+            block_signature: [0; 64],
+            nonce: 0,
+            base_target: 18_325_193_796,
+            block_ats: None,
+            transactions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn genesis_block_has_mainnet_id() {
+        let block = genesis_block();
+
+        assert_eq!(block.to_bytes().len(), 232);
+        assert_eq!(
+            BlockId::from_full_hash(&block.full_hash()),
+            BlockId::from(3_444_294_670_862_540_038)
+        );
+    }
+
+    #[test]
+    fn previous_block_hash_links_to_previous_block_id() {
+        // The fixture is a mainnet block, which references its previous block by id and hash
+        let block = Block::try_from(b1_block()).unwrap();
+
+        assert_eq!(
+            BlockId::from_full_hash(&block.previous_block_hash.unwrap()),
+            BlockId::from(18_369_301_224_889_401_486)
+        );
+        assert_eq!(
+            block.previous_block_id,
+            BlockId::from(18_369_301_224_889_401_486)
+        );
+    }
+
+    #[test]
+    fn verify_signature_checks_generator_signature() {
+        let mut block = Block::try_from(b1_block()).unwrap();
+        block.generator_public_key = crypto::public_key("generator passphrase");
+        block.block_signature = crypto::sign(&block.unsigned_bytes(), "generator passphrase");
+        assert!(block.verify_signature(&block.generator_public_key));
+        assert!(!block.verify_signature(&crypto::public_key("pool passphrase")));
+
+        block.nonce += 1;
+        assert!(!block.verify_signature(&block.generator_public_key));
+    }
+
+    #[test]
+    fn fixture_signatures_match_mainnet() {
+        let block = Block::try_from(b1_block()).unwrap();
+
+        // The fixture was forged by a pool miner, so the pool signed it rather than the generator
+        assert!(!block.verify_signature(&block.generator_public_key));
+        assert!(block.transactions.iter().all(|t| t.verify_signature()));
+    }
+
+    #[test]
+    fn to_bytes_follows_version_layout() {
+        let mut block = Block::try_from(b1_block()).unwrap();
+
+        // 20 header bytes, 16 for the totals, 4 + 96 for the payload and generator, 32 for the
+        // previous block hash, 8 for the nonce, the ATs and the signature
+        let bytes = block.to_bytes();
+        assert_eq!(bytes.len(), 176 + 48 + 64);
+        assert_eq!(&bytes[..4], &4i32.to_le_bytes());
+        assert_eq!(&bytes[20..28], &block.total_amount.nqt().to_le_bytes());
+        assert_eq!(&bytes[bytes.len() - 64..], &block.block_signature);
+        assert_eq!(block.unsigned_bytes(), &bytes[..bytes.len() - 64]);
 
-impl From<Block> for ExchangeableBlock {
-    fn from(value: Block) -> Self {
-        todo!()
+        // Version 2 blocks hold their totals in whole Signa
+        block.version = 2;
+        let bytes = block.to_bytes();
+        assert_eq!(bytes.len(), 176 - 8 + 48 + 64);
+        assert_eq!(&bytes[20..24], &209u32.to_le_bytes());
     }
 }
@@ -1,14 +1,24 @@
-use std::time::Duration;
+use std::{str::FromStr, time::Duration};
 
 use actix_web::ResponseError;
 use anyhow::{Context, Result};
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
 use surrealdb::{
     engine::any::Any,
+    error::Db,
     sql::statements::{BeginStatement, CommitStatement},
     Response, Surreal,
 };
 
-use super::p2p::{PeerAddress, PeerInfo};
+use super::{
+    p2p::{B1Block, B1Transaction, PeerAddress, PeerInfo, Transaction},
+    AccountId,
+};
+
+/// How many blocks follow the one confirming a reward recipient assignment before it takes
+/// effect.
+const REWARD_RECIPIENT_ASSIGNMENT_WAIT: u64 = 4;
 
 #[derive(Clone, Debug)]
 pub struct Datastore {
@@ -28,6 +38,50 @@ impl Datastore {
         self.db.clone()
     }
 
+    /// Adds a transaction to the unconfirmed transaction pool.
+    ///
+    /// Transactions are keyed by their ID. Returns `false` if the pool already held the
+    /// transaction, or if it is already in the local chain.
+    pub async fn add_unconfirmed_transaction(
+        &self,
+        transaction: &B1Transaction,
+    ) -> Result<bool, DatastoreError> {
+        let transaction_id = Transaction::try_from(transaction.clone())
+            .context("unable to decode the unconfirmed transaction")?
+            .id
+            .into();
+        if !self
+            .get_confirmed_transaction_ids(&[transaction_id])
+            .await?
+            .is_empty()
+        {
+            return Ok(false);
+        }
+
+        let mut response = self
+            .db
+            .query(
+                r#"
+                INSERT IGNORE INTO unconfirmed_transaction {
+                    id: $transaction_id,
+                    expiration: $expiration,
+                    transaction: $transaction
+                }
+            "#,
+            )
+            .bind(("transaction_id", transaction_id.to_string()))
+            .bind(("expiration", transaction.expiration()))
+            .bind(("transaction", transaction.clone()))
+            .await
+            .context("unable to add the transaction to the unconfirmed pool")?;
+
+        let added = response
+            .take::<Vec<u64>>("expiration")
+            .context("unable to deserialize the added transaction from the response")?;
+
+        Ok(!added.is_empty())
+    }
+
     /// Blacklists the provided peer. It will begin with 10 minutes and keeps track of the number
     /// of times the peer was blacklisted. For each instance, the timer grows by 10 minutes until a
     /// maximum of 24 hours.
@@ -93,7 +147,7 @@ impl Datastore {
                 UPDATE peer
                 SET
                     blacklist.count = 0,
-                    blacklist.until = null,
+                    blacklist.until = null
                     WHERE announced_address = $peer
             "#,
             )
@@ -104,6 +158,360 @@ impl Datastore {
         Ok(response)
     }
 
+    /// Returns the block with the given ID, if it is in the local chain.
+    pub async fn get_block(&self, block_id: u64) -> Result<Option<B1Block>, DatastoreError> {
+        let mut response = self
+            .db
+            .query(
+                r#"
+                SELECT block
+                FROM block
+                WHERE block_id = $block_id
+                LIMIT 1
+            "#,
+            )
+            .bind(("block_id", block_id.to_string()))
+            .await
+            .context(format!("unable to get block {}", block_id))?;
+
+        let block = response
+            .take::<Option<B1Block>>("block")
+            .context("unable to deserialize the block from the response")?;
+
+        Ok(block)
+    }
+
+    /// Returns the block at the given height in the local chain, if there is one.
+    pub async fn get_block_at_height(
+        &self,
+        height: u64,
+    ) -> Result<Option<B1Block>, DatastoreError> {
+        let mut response = self
+            .db
+            .query(
+                r#"
+                SELECT block
+                FROM block
+                WHERE height = $height
+                LIMIT 1
+            "#,
+            )
+            .bind(("height", height))
+            .await
+            .context(format!("unable to get the block at height {}", height))?;
+
+        let block = response
+            .take::<Option<B1Block>>("block")
+            .context("unable to deserialize the block from the response")?;
+
+        Ok(block)
+    }
+
+    /// Returns up to `limit` block IDs that follow the given block in the local chain, in order.
+    ///
+    /// Returns an empty list if the block is not in the local chain.
+    pub async fn get_block_ids_after(
+        &self,
+        block_id: u64,
+        limit: u32,
+    ) -> Result<Vec<u64>, DatastoreError> {
+        let Some(height) = self.get_block_height(block_id).await? else {
+            return Ok(Vec::new());
+        };
+
+        let mut response = self
+            .db
+            .query(
+                r#"
+                SELECT block_id, height
+                FROM block
+                WHERE height > $height
+                ORDER BY height
+                LIMIT $limit
+            "#,
+            )
+            .bind(("height", height))
+            .bind(("limit", limit))
+            .await
+            .context(format!("unable to get the block ids after {}", block_id))?;
+
+        let block_ids = response
+            .take::<Vec<String>>("block_id")
+            .context("unable to deserialize the block ids from the response")?
+            .iter()
+            .map(|id| u64::from_str(id))
+            .collect::<Result<Vec<_>, _>>()
+            .context("couldn't convert string to a block id")?;
+
+        Ok(block_ids)
+    }
+
+    /// Returns up to `limit` blocks that follow the given block in the local chain, in order.
+    ///
+    /// Returns an empty list if the block is not in the local chain.
+    pub async fn get_blocks_after(
+        &self,
+        block_id: u64,
+        limit: u32,
+    ) -> Result<Vec<B1Block>, DatastoreError> {
+        let Some(height) = self.get_block_height(block_id).await? else {
+            return Ok(Vec::new());
+        };
+
+        self.get_blocks_from_height(height + 1, limit).await
+    }
+
+    /// Returns the public key a block forged by the given generator at `height` must be signed
+    /// with.
+    ///
+    /// That's the generator's own key, unless it assigned its rewards to another account such as
+    /// a pool, which then signs its blocks. An assignment takes effect once
+    /// [`REWARD_RECIPIENT_ASSIGNMENT_WAIT`] blocks have followed the one it's confirmed in, until
+    /// then the previous recipient signs. Returns `None` if the recipient's public key isn't known
+    /// from a transaction it sent.
+    pub async fn get_block_signer_public_key(
+        &self,
+        generator_public_key: &[u8; 32],
+        height: u64,
+    ) -> Result<Option<[u8; 32]>, DatastoreError> {
+        let generator = AccountId::from_public_key(generator_public_key).to_string();
+        let mut response = self
+            .db
+            .query(
+                r#"
+                SELECT height, index, recipient_id
+                FROM confirmed_transaction
+                WHERE sender_id = $generator
+                    AND transaction.type = 20
+                    AND transaction.subtype = 0
+                    AND height < $height
+                ORDER BY height DESC, index DESC
+                LIMIT 2
+            "#,
+            )
+            .bind(("generator", generator.clone()))
+            .bind(("height", height))
+            .await
+            .context(format!(
+                "unable to get the reward recipient of {}",
+                generator
+            ))?;
+
+        let assignments = response
+            .take::<Vec<RewardRecipientAssignmentRecord>>(0)
+            .context("unable to deserialize the reward recipient assignments from the response")?;
+
+        // The latest assignment only replaces the one before it once it takes effect
+        let recipient = match assignments.as_slice() {
+            [latest, ..] if height > latest.height + REWARD_RECIPIENT_ASSIGNMENT_WAIT => {
+                &latest.recipient_id
+            }
+            [_, previous] => &previous.recipient_id,
+            _ => return Ok(Some(*generator_public_key)),
+        };
+        if *recipient == generator {
+            return Ok(Some(*generator_public_key));
+        }
+
+        let mut response = self
+            .db
+            .query(
+                r#"
+                SELECT transaction.senderPublicKey AS sender_public_key
+                FROM confirmed_transaction
+                WHERE sender_id = $recipient
+                LIMIT 1
+            "#,
+            )
+            .bind(("recipient", recipient.clone()))
+            .await
+            .context(format!("unable to get the public key of {}", recipient))?;
+
+        let public_key = response
+            .take::<Option<String>>("sender_public_key")
+            .context("unable to deserialize the public key from the response")?
+            .map(|key| {
+                hex::decode(key)
+                    .ok()
+                    .and_then(|k| <[u8; 32]>::try_from(k).ok())
+            })
+            .map(|key| key.context(format!("{} has a malformed public key", recipient)))
+            .transpose()?;
+
+        Ok(public_key)
+    }
+
+    /// Returns up to `limit` of the blocks the account forged, newest first.
+    pub async fn get_blocks_by_generator(
+        &self,
+        account: AccountId,
+        limit: u32,
+    ) -> Result<Vec<B1Block>, DatastoreError> {
+        let mut response = self
+            .db
+            .query(
+                r#"
+                SELECT block, height
+                FROM block
+                WHERE generator_id = $account
+                ORDER BY height DESC
+                LIMIT $limit
+            "#,
+            )
+            .bind(("account", account.to_string()))
+            .bind(("limit", limit))
+            .await
+            .context(format!("unable to get the blocks forged by {}", account))?;
+
+        let blocks = response
+            .take::<Vec<B1Block>>("block")
+            .context("unable to deserialize the blocks from the response")?;
+
+        Ok(blocks)
+    }
+
+    /// Returns up to `limit` blocks from the local chain, in order, starting at `height`.
+    pub async fn get_blocks_from_height(
+        &self,
+        height: u64,
+        limit: u32,
+    ) -> Result<Vec<B1Block>, DatastoreError> {
+        let mut response = self
+            .db
+            .query(
+                r#"
+                SELECT block, height
+                FROM block
+                WHERE height >= $height
+                ORDER BY height
+                LIMIT $limit
+            "#,
+            )
+            .bind(("height", height))
+            .bind(("limit", limit))
+            .await
+            .context(format!("unable to get the blocks from height {}", height))?;
+
+        let blocks = response
+            .take::<Vec<B1Block>>("block")
+            .context("unable to deserialize the blocks from the response")?;
+
+        Ok(blocks)
+    }
+
+    /// Returns the ID of the block at the given height in the local chain, if there is one.
+    pub async fn get_block_id_at_height(&self, height: u64) -> Result<Option<u64>, DatastoreError> {
+        let mut response = self
+            .db
+            .query(
+                r#"
+                SELECT block_id
+                FROM block
+                WHERE height = $height
+                LIMIT 1
+            "#,
+            )
+            .bind(("height", height))
+            .await
+            .context(format!("unable to get the block at height {}", height))?;
+
+        let block_id = response
+            .take::<Option<String>>("block_id")
+            .context("unable to deserialize the block id from the response")?
+            .map(|id| u64::from_str(&id))
+            .transpose()
+            .context("couldn't convert string to a block id")?;
+
+        Ok(block_id)
+    }
+
+    /// Returns the height of the block with the given ID, if it is in the local chain.
+    pub async fn get_block_height(&self, block_id: u64) -> Result<Option<u64>, DatastoreError> {
+        let mut response = self
+            .db
+            .query(
+                r#"
+                SELECT height
+                FROM block
+                WHERE block_id = $block_id
+                LIMIT 1
+            "#,
+            )
+            .bind(("block_id", block_id.to_string()))
+            .await
+            .context(format!("unable to get the height of block {}", block_id))?;
+
+        let height = response
+            .take::<Option<u64>>("height")
+            .context("unable to deserialize the height from the response")?;
+
+        Ok(height)
+    }
+
+    /// Returns the ID, height and cumulative difficulty of the highest block in the local chain,
+    /// if there is one.
+    pub async fn get_chain_tip(&self) -> Result<Option<(u64, u64, BigUint)>, DatastoreError> {
+        let mut response = self
+            .db
+            .query(
+                r#"
+                SELECT block_id, height, cumulative_difficulty
+                FROM block
+                ORDER BY height DESC
+                LIMIT 1
+            "#,
+            )
+            .await
+            .context("unable to get the chain tip from the database")?;
+
+        let block_id = response
+            .take::<Option<String>>("block_id")
+            .context("unable to deserialize the block id from the response")?
+            .map(|id| u64::from_str(&id))
+            .transpose()
+            .context("couldn't convert string to a block id")?;
+        let height = response
+            .take::<Option<u64>>("height")
+            .context("unable to deserialize the height from the response")?;
+        let cumulative_difficulty = response
+            .take::<Option<String>>("cumulative_difficulty")
+            .context("unable to deserialize the cumulative difficulty from the response")?
+            .map(|cd| BigUint::from_str(&cd))
+            .transpose()
+            .context("couldn't convert string to a BigUint")?;
+
+        Ok(block_id
+            .zip(height)
+            .zip(cumulative_difficulty)
+            .map(|((block_id, height), cd)| (block_id, height, cd)))
+    }
+
+    /// Returns the announced address of the peer last seen at the given IP address, if any.
+    pub async fn get_peer_address_by_ip(
+        &self,
+        ip_address: &str,
+    ) -> Result<Option<PeerAddress>, DatastoreError> {
+        let mut response = self
+            .db
+            .query(
+                r#"
+                SELECT announced_address
+                FROM peer
+                WHERE ip_address = $ip_address
+                LIMIT 1
+            "#,
+            )
+            .bind(("ip_address", ip_address.to_string()))
+            .await
+            .context(format!("unable to get the peer for {}", ip_address))?;
+
+        let peer_address = response
+            .take::<Option<PeerAddress>>("announced_address")
+            .context("unable to deserialize the peer from the response")?;
+
+        Ok(peer_address)
+    }
+
     /// Returns a list of peers whose last seen time is older than the [`Duration`].
     pub async fn get_peers_last_seen_before(&self, duration: Duration) -> Result<Vec<PeerAddress>> {
         let mut response = self
@@ -126,6 +534,38 @@ impl Datastore {
         Ok(peers)
     }
 
+    /// Returns up to `limit` peers that are not blacklisted, have been seen within the
+    /// [`Duration`], and have asked for their address to be shared with other peers.
+    pub async fn get_shareable_peers(
+        &self,
+        seen_within: Duration,
+        limit: u32,
+    ) -> Result<Vec<PeerAddress>, DatastoreError> {
+        let mut response = self
+            .db
+            .query(
+                r#"
+                SELECT announced_address
+                FROM peer
+                WHERE
+                    (blacklist.until IS none OR blacklist.until < time::now())
+                    AND share_address = true
+                    AND last_seen > time::now() - $seen_within
+                LIMIT $limit
+            "#,
+            )
+            .bind(("seen_within", surrealdb::sql::Duration::from(seen_within)))
+            .bind(("limit", limit))
+            .await
+            .context("unable to fetch shareable peers from the database")?;
+
+        let peers = response
+            .take::<Vec<PeerAddress>>("announced_address")
+            .context("unable to deserialize the peers from the response")?;
+
+        Ok(peers)
+    }
+
     /// Returns a randomized peer from the database.
     ///
     /// Returns an error if there was a problem or if there are no peers in the database.
@@ -186,6 +626,157 @@ impl Datastore {
         Ok(peer_address)
     }
 
+    /// Returns which of the given transaction IDs are already in the local chain.
+    pub async fn get_confirmed_transaction_ids(
+        &self,
+        transaction_ids: &[u64],
+    ) -> Result<Vec<u64>, DatastoreError> {
+        let mut response = self
+            .db
+            .query(
+                r#"
+                SELECT transaction_id
+                FROM confirmed_transaction
+                WHERE transaction_id IN $transaction_ids
+            "#,
+            )
+            .bind((
+                "transaction_ids",
+                transaction_ids
+                    .iter()
+                    .map(u64::to_string)
+                    .collect::<Vec<_>>(),
+            ))
+            .await
+            .context("unable to look up confirmed transaction ids")?;
+
+        let transaction_ids = response
+            .take::<Vec<String>>("transaction_id")
+            .context("unable to deserialize the transaction ids from the response")?
+            .iter()
+            .map(|id| u64::from_str(id))
+            .collect::<Result<Vec<_>, _>>()
+            .context("couldn't convert string to a transaction id")?;
+
+        Ok(transaction_ids)
+    }
+
+    /// Returns the transaction with the given ID, if it is in the local chain.
+    pub async fn get_transaction(
+        &self,
+        transaction_id: u64,
+    ) -> Result<Option<B1Transaction>, DatastoreError> {
+        let mut response = self
+            .db
+            .query(
+                r#"
+                SELECT transaction
+                FROM confirmed_transaction
+                WHERE transaction_id = $transaction_id
+                LIMIT 1
+            "#,
+            )
+            .bind(("transaction_id", transaction_id.to_string()))
+            .await
+            .context(format!("unable to get transaction {}", transaction_id))?;
+
+        let transaction = response
+            .take::<Option<B1Transaction>>("transaction")
+            .context("unable to deserialize the transaction from the response")?;
+
+        Ok(transaction)
+    }
+
+    /// Returns the transaction with the given full hash, if it is in the local chain.
+    pub async fn get_transaction_by_full_hash(
+        &self,
+        full_hash: &[u8; 32],
+    ) -> Result<Option<B1Transaction>, DatastoreError> {
+        let full_hash = hex::encode(full_hash);
+        let mut response = self
+            .db
+            .query(
+                r#"
+                SELECT transaction
+                FROM confirmed_transaction
+                WHERE full_hash = $full_hash
+                LIMIT 1
+            "#,
+            )
+            .bind(("full_hash", full_hash.clone()))
+            .await
+            .context(format!(
+                "unable to get transaction with full hash {}",
+                full_hash
+            ))?;
+
+        let transaction = response
+            .take::<Option<B1Transaction>>("transaction")
+            .context("unable to deserialize the transaction from the response")?;
+
+        Ok(transaction)
+    }
+
+    /// Returns up to `limit` of the transactions the account sent or received in the local
+    /// chain, newest first.
+    pub async fn get_transactions_by_account(
+        &self,
+        account: AccountId,
+        limit: u32,
+    ) -> Result<Vec<B1Transaction>, DatastoreError> {
+        let mut response = self
+            .db
+            .query(
+                r#"
+                SELECT transaction, height, index
+                FROM confirmed_transaction
+                WHERE sender_id = $account OR recipient_id = $account
+                ORDER BY height DESC, index DESC
+                LIMIT $limit
+            "#,
+            )
+            .bind(("account", account.to_string()))
+            .bind(("limit", limit))
+            .await
+            .context(format!("unable to get the transactions of {}", account))?;
+
+        let transactions = response
+            .take::<Vec<B1Transaction>>("transaction")
+            .context("unable to deserialize the transactions from the response")?;
+
+        Ok(transactions)
+    }
+
+    /// Returns up to `limit` unconfirmed transactions that have not expired by `now`, oldest
+    /// first.
+    pub async fn get_unconfirmed_transactions(
+        &self,
+        now: u64,
+        limit: u32,
+    ) -> Result<Vec<B1Transaction>, DatastoreError> {
+        let mut response = self
+            .db
+            .query(
+                r#"
+                SELECT transaction, transaction.timestamp
+                FROM unconfirmed_transaction
+                WHERE expiration > $now
+                ORDER BY transaction.timestamp
+                LIMIT $limit
+            "#,
+            )
+            .bind(("now", now))
+            .bind(("limit", limit))
+            .await
+            .context("unable to get the unconfirmed transactions")?;
+
+        let transactions = response
+            .take::<Vec<B1Transaction>>("transaction")
+            .context("unable to deserialize the unconfirmed transactions from the response")?;
+
+        Ok(transactions)
+    }
+
     /// Increments the number of attempts to contact a peer since a peer was last seen.
     pub async fn increment_attempts_since_last_seen(
         &self,
@@ -211,6 +802,101 @@ impl Datastore {
         Ok(response)
     }
 
+    /// Removes unconfirmed transactions that have expired by `now` from the pool.
+    pub async fn remove_expired_unconfirmed_transactions(
+        &self,
+        now: u64,
+    ) -> Result<Response, DatastoreError> {
+        let response = self
+            .db
+            .query(BeginStatement::default())
+            .query(
+                r#"
+                DELETE unconfirmed_transaction
+                WHERE expiration <= $now
+            "#,
+            )
+            .bind(("now", now))
+            .query(CommitStatement::default())
+            .await
+            .context("unable to remove expired unconfirmed transactions")?;
+        Ok(response)
+    }
+
+    /// Adds a block to the local chain at the given height, along with the cumulative
+    /// difficulty of the chain up to and including it.
+    ///
+    /// The block's transactions are stored alongside it so they can be looked up on their own.
+    /// Either the block and all its transactions are stored, or none of them are. Returns
+    /// [`DatastoreError::HeightTaken`] if there already is a block at `height`.
+    pub async fn store_block(
+        &self,
+        block: &B1Block,
+        block_id: u64,
+        height: u64,
+        cumulative_difficulty: &BigUint,
+    ) -> Result<Response, DatastoreError> {
+        let generator_public_key = hex::decode(&block.generator_public_key)
+            .ok()
+            .and_then(|k| <[u8; 32]>::try_from(k).ok())
+            .context(format!("block {} has a malformed generator key", block_id))?;
+        let transactions = block
+            .transactions
+            .iter()
+            .enumerate()
+            .map(|(index, transaction)| {
+                ConfirmedTransactionRecord::new(transaction, block_id, height, index)
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .context(format!("block {} has a malformed transaction", block_id))?;
+
+        let response = self
+            .db
+            .query(BeginStatement::default())
+            .query(
+                r#"
+                CREATE block
+                CONTENT {
+                    block_id: $block_id,
+                    height: $height,
+                    generator_id: $generator_id,
+                    cumulative_difficulty: $cumulative_difficulty,
+                    block: $block
+                }
+            "#,
+            )
+            .query(
+                r#"
+                FOR $transaction IN $transactions {
+                    CREATE confirmed_transaction CONTENT $transaction
+                }
+            "#,
+            )
+            .bind(("block_id", block_id.to_string()))
+            .bind(("height", height))
+            .bind((
+                "generator_id",
+                AccountId::from_public_key(&generator_public_key).to_string(),
+            ))
+            .bind(("cumulative_difficulty", cumulative_difficulty.to_string()))
+            .bind(("block", block.clone()))
+            .bind(("transactions", transactions))
+            .query(CommitStatement::default())
+            .await
+            .context(format!("unable to store block {}", block_id))?;
+
+        let response = match response.check() {
+            Err(surrealdb::Error::Db(Db::IndexExists { index, .. }))
+                if index == "unique_block_height" =>
+            {
+                return Err(DatastoreError::HeightTaken(height))
+            }
+            response => response.context(format!("unable to store block {}", block_id))?,
+        };
+
+        Ok(response)
+    }
+
     /// Provide a [`PeerInfo`] to update a peer's information.
     pub async fn update_peer_info(
         &self,
@@ -256,9 +942,54 @@ impl Datastore {
     }
 }
 
+/// A transaction of a stored block, with the fields it can be looked up by.
+#[derive(Serialize)]
+struct ConfirmedTransactionRecord {
+    transaction_id: String,
+    full_hash: String,
+    block_id: String,
+    height: u64,
+    /// The transaction's position in its block.
+    index: usize,
+    sender_id: String,
+    recipient_id: Option<String>,
+    transaction: B1Transaction,
+}
+
+impl ConfirmedTransactionRecord {
+    fn new(
+        transaction: &B1Transaction,
+        block_id: u64,
+        height: u64,
+        index: usize,
+    ) -> Result<Self, anyhow::Error> {
+        let decoded = Transaction::try_from(transaction.clone())?;
+        Ok(Self {
+            transaction_id: decoded.id.to_string(),
+            full_hash: hex::encode(decoded.full_hash),
+            block_id: block_id.to_string(),
+            height,
+            index,
+            sender_id: AccountId::from_public_key(&decoded.sender_public_key).to_string(),
+            recipient_id: decoded.recipient.map(|r| r.to_string()),
+            transaction: transaction.clone(),
+        })
+    }
+}
+
+/// A reward recipient assignment, as looked up by [`Datastore::get_block_signer_public_key`].
+#[derive(Deserialize)]
+struct RewardRecipientAssignmentRecord {
+    height: u64,
+    recipient_id: String,
+}
+
 /// Represents a Datastore error.
 #[derive(thiserror::Error)]
 pub enum DatastoreError {
+    /// Another block was stored at the height first.
+    #[error("a block is already stored at height {0}")]
+    HeightTaken(u64),
     /// An unexpected error. Will contain an [`anyhow::Error`] with additional details.
     #[error(transparent)]
     UnexpectedError(#[from] anyhow::Error),
@@ -1,14 +1,19 @@
+mod attachment;
 mod b1_block;
 mod b1_transaction;
 mod block_id;
 mod peer_address;
 mod peer_info;
 mod transaction;
+mod transaction_id;
+mod transaction_type;
 
+pub use attachment::*;
 pub use b1_block::B1Block;
 pub use b1_transaction::B1Transaction;
-pub use b1_transaction::B1TransactionAttachment;
 pub use block_id::BlockId;
 pub use peer_address::PeerAddress;
 pub use peer_info::PeerInfo;
-pub use transaction::Transaction;
+pub use transaction::{Transaction, TransactionConversionError};
+pub use transaction_id::TransactionId;
+pub use transaction_type::{TransactionType, UnknownTransactionType};
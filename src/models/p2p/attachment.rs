@@ -0,0 +1,1018 @@
+//! Transaction attachments and appendages, in both the JSON form exchanged between peers and
+//! the binary form used for transaction bytes.
+//!
+//! On the wire, a transaction's `attachment` object holds the type-specific attachment fields
+//! next to the fields of any appendages (messages and public key announcements). Each part
+//! with a version above 0 announces it with a `version.<Name>` field.
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{Map, Value};
+use serde_with::{hex::Hex, serde_as, DisplayFromStr, PickFirst};
+
+use super::TransactionType;
+
+/// The attachment of a transaction, which carries the data specific to its type and subtype.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Attachment {
+    pub version: u8,
+    pub body: AttachmentBody,
+}
+
+/// The data specific to each type and subtype of transaction.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AttachmentBody {
+    OrdinaryPayment,
+    MultiOut(MultiOut),
+    MultiSameOut(MultiSameOut),
+    ArbitraryMessage,
+    AliasAssignment(AliasAssignment),
+    AccountInfo(AccountInfo),
+    AliasSell(AliasSell),
+    AliasBuy(AliasBuy),
+    AssetIssuance(AssetIssuance),
+    AssetTransfer(AssetTransfer),
+    AskOrderPlacement(OrderPlacement),
+    BidOrderPlacement(OrderPlacement),
+    AskOrderCancellation(OrderCancellation),
+    BidOrderCancellation(OrderCancellation),
+    AssetMint(AssetMint),
+    AddTreasuryAccount,
+    DistributeToHolders(DistributeToHolders),
+    AssetMultiTransfer(AssetMultiTransfer),
+    DigitalGoodsListing(DigitalGoodsListing),
+    DigitalGoodsDelisting(DigitalGoodsReference),
+    DigitalGoodsPriceChange(DigitalGoodsPriceChange),
+    DigitalGoodsQuantityChange(DigitalGoodsQuantityChange),
+    DigitalGoodsPurchase(DigitalGoodsPurchase),
+    DigitalGoodsDelivery(DigitalGoodsDelivery),
+    DigitalGoodsFeedback(PurchaseReference),
+    DigitalGoodsRefund(DigitalGoodsRefund),
+    EffectiveBalanceLeasing(EffectiveBalanceLeasing),
+    RewardRecipientAssignment,
+    CommitmentAdd(Commitment),
+    CommitmentRemove(Commitment),
+    EscrowCreation(EscrowCreation),
+    EscrowSign(EscrowDecision),
+    EscrowResult(EscrowDecision),
+    SubscriptionSubscribe(SubscriptionSubscribe),
+    SubscriptionCancel(SubscriptionReference),
+    SubscriptionPayment(SubscriptionReference),
+    AutomatedTransactionsCreation(AutomatedTransactionsCreation),
+    AutomatedTransactionsPayment,
+}
+
+impl Attachment {
+    /// Reads the attachment of a transaction of the given type from the JSON `attachment`
+    /// object.
+    pub fn from_json(
+        transaction_type: TransactionType,
+        json: &Map<String, Value>,
+    ) -> Result<Self, AttachmentError> {
+        use AttachmentBody as B;
+        use TransactionType as T;
+
+        let body = match transaction_type {
+            T::OrdinaryPayment => B::OrdinaryPayment,
+            T::MultiOut => B::MultiOut(parse(json)?),
+            T::MultiSameOut => B::MultiSameOut(parse(json)?),
+            T::ArbitraryMessage => B::ArbitraryMessage,
+            T::AliasAssignment => B::AliasAssignment(parse(json)?),
+            T::AccountInfo => B::AccountInfo(parse(json)?),
+            T::AliasSell => B::AliasSell(parse(json)?),
+            T::AliasBuy => B::AliasBuy(parse(json)?),
+            T::AssetIssuance => B::AssetIssuance(parse(json)?),
+            T::AssetTransfer => B::AssetTransfer(parse(json)?),
+            T::AskOrderPlacement => B::AskOrderPlacement(parse(json)?),
+            T::BidOrderPlacement => B::BidOrderPlacement(parse(json)?),
+            T::AskOrderCancellation => B::AskOrderCancellation(parse(json)?),
+            T::BidOrderCancellation => B::BidOrderCancellation(parse(json)?),
+            T::AssetMint => B::AssetMint(parse(json)?),
+            T::AddTreasuryAccount => B::AddTreasuryAccount,
+            T::DistributeToHolders => B::DistributeToHolders(parse(json)?),
+            T::AssetMultiTransfer => B::AssetMultiTransfer(parse(json)?),
+            T::DigitalGoodsListing => B::DigitalGoodsListing(parse(json)?),
+            T::DigitalGoodsDelisting => B::DigitalGoodsDelisting(parse(json)?),
+            T::DigitalGoodsPriceChange => B::DigitalGoodsPriceChange(parse(json)?),
+            T::DigitalGoodsQuantityChange => B::DigitalGoodsQuantityChange(parse(json)?),
+            T::DigitalGoodsPurchase => B::DigitalGoodsPurchase(parse(json)?),
+            T::DigitalGoodsDelivery => B::DigitalGoodsDelivery(parse(json)?),
+            T::DigitalGoodsFeedback => B::DigitalGoodsFeedback(parse(json)?),
+            T::DigitalGoodsRefund => B::DigitalGoodsRefund(parse(json)?),
+            T::EffectiveBalanceLeasing => B::EffectiveBalanceLeasing(parse(json)?),
+            T::RewardRecipientAssignment => B::RewardRecipientAssignment,
+            T::CommitmentAdd => B::CommitmentAdd(parse(json)?),
+            T::CommitmentRemove => B::CommitmentRemove(parse(json)?),
+            T::EscrowCreation => B::EscrowCreation(parse(json)?),
+            T::EscrowSign => B::EscrowSign(parse(json)?),
+            T::EscrowResult => B::EscrowResult(parse(json)?),
+            T::SubscriptionSubscribe => B::SubscriptionSubscribe(parse(json)?),
+            T::SubscriptionCancel => B::SubscriptionCancel(parse(json)?),
+            T::SubscriptionPayment => B::SubscriptionPayment(parse(json)?),
+            T::AutomatedTransactionsCreation => B::AutomatedTransactionsCreation(parse(json)?),
+            T::AutomatedTransactionsPayment => B::AutomatedTransactionsPayment,
+        };
+
+        let version = match body.name() {
+            Some(name) => read_version(json, name)?,
+            None => 0,
+        };
+
+        Ok(Self { version, body })
+    }
+
+    /// Writes the attachment's fields into a JSON `attachment` object.
+    pub fn to_json(&self, json: &mut Map<String, Value>) -> Result<(), AttachmentError> {
+        use AttachmentBody as B;
+
+        let fields = match &self.body {
+            B::OrdinaryPayment
+            | B::ArbitraryMessage
+            | B::AddTreasuryAccount
+            | B::RewardRecipientAssignment
+            | B::AutomatedTransactionsPayment => Value::Object(Map::new()),
+            B::MultiOut(a) => serde_json::to_value(a)?,
+            B::MultiSameOut(a) => serde_json::to_value(a)?,
+            B::AliasAssignment(a) => serde_json::to_value(a)?,
+            B::AccountInfo(a) => serde_json::to_value(a)?,
+            B::AliasSell(a) => serde_json::to_value(a)?,
+            B::AliasBuy(a) => serde_json::to_value(a)?,
+            B::AssetIssuance(a) => serde_json::to_value(a)?,
+            B::AssetTransfer(a) => serde_json::to_value(a)?,
+            B::AskOrderPlacement(a) | B::BidOrderPlacement(a) => serde_json::to_value(a)?,
+            B::AskOrderCancellation(a) | B::BidOrderCancellation(a) => serde_json::to_value(a)?,
+            B::AssetMint(a) => serde_json::to_value(a)?,
+            B::DistributeToHolders(a) => serde_json::to_value(a)?,
+            B::AssetMultiTransfer(a) => serde_json::to_value(a)?,
+            B::DigitalGoodsListing(a) => serde_json::to_value(a)?,
+            B::DigitalGoodsDelisting(a) => serde_json::to_value(a)?,
+            B::DigitalGoodsPriceChange(a) => serde_json::to_value(a)?,
+            B::DigitalGoodsQuantityChange(a) => serde_json::to_value(a)?,
+            B::DigitalGoodsPurchase(a) => serde_json::to_value(a)?,
+            B::DigitalGoodsDelivery(a) => serde_json::to_value(a)?,
+            B::DigitalGoodsFeedback(a) => serde_json::to_value(a)?,
+            B::DigitalGoodsRefund(a) => serde_json::to_value(a)?,
+            B::EffectiveBalanceLeasing(a) => serde_json::to_value(a)?,
+            B::CommitmentAdd(a) | B::CommitmentRemove(a) => serde_json::to_value(a)?,
+            B::EscrowCreation(a) => serde_json::to_value(a)?,
+            B::EscrowSign(a) | B::EscrowResult(a) => serde_json::to_value(a)?,
+            B::SubscriptionSubscribe(a) => serde_json::to_value(a)?,
+            B::SubscriptionCancel(a) | B::SubscriptionPayment(a) => serde_json::to_value(a)?,
+            B::AutomatedTransactionsCreation(a) => serde_json::to_value(a)?,
+        };
+        if let Value::Object(fields) = fields {
+            json.extend(fields);
+        }
+        if let Some(name) = self.body.name() {
+            write_version(json, name, self.version);
+        }
+
+        Ok(())
+    }
+
+    /// Appends the attachment's binary form to `bytes`.
+    pub fn put_bytes(&self, bytes: &mut Vec<u8>) {
+        use AttachmentBody as B;
+
+        if self.version > 0 {
+            bytes.push(self.version);
+        }
+        match &self.body {
+            B::OrdinaryPayment
+            | B::ArbitraryMessage
+            | B::AddTreasuryAccount
+            | B::RewardRecipientAssignment
+            | B::AutomatedTransactionsPayment => {}
+            B::MultiOut(a) => {
+                bytes.push(a.recipients.len() as u8);
+                for (recipient, amount_nqt) in &a.recipients {
+                    put_u64(bytes, *recipient);
+                    put_u64(bytes, *amount_nqt);
+                }
+            }
+            B::MultiSameOut(a) => {
+                bytes.push(a.recipients.len() as u8);
+                for recipient in &a.recipients {
+                    put_u64(bytes, *recipient);
+                }
+            }
+            B::AliasAssignment(a) => {
+                put_byte_string(bytes, &a.alias);
+                put_short_string(bytes, &a.uri);
+            }
+            B::AccountInfo(a) => {
+                put_byte_string(bytes, &a.name);
+                put_short_string(bytes, &a.description);
+            }
+            B::AliasSell(a) => {
+                put_byte_string(bytes, &a.alias);
+                put_u64(bytes, a.price_nqt);
+            }
+            B::AliasBuy(a) => put_byte_string(bytes, &a.alias),
+            B::AssetIssuance(a) => {
+                put_byte_string(bytes, &a.name);
+                put_short_string(bytes, &a.description);
+                put_u64(bytes, a.quantity_qnt);
+                bytes.push(a.decimals);
+                if self.version > 1 {
+                    bytes.push(u8::from(a.mintable));
+                }
+            }
+            B::AssetTransfer(a) => {
+                put_u64(bytes, a.asset);
+                put_u64(bytes, a.quantity_qnt);
+                if self.version == 0 {
+                    put_short_string(bytes, a.comment.as_deref().unwrap_or_default());
+                }
+            }
+            B::AskOrderPlacement(a) | B::BidOrderPlacement(a) => {
+                put_u64(bytes, a.asset);
+                put_u64(bytes, a.quantity_qnt);
+                put_u64(bytes, a.price_nqt);
+            }
+            B::AskOrderCancellation(a) | B::BidOrderCancellation(a) => put_u64(bytes, a.order),
+            B::AssetMint(a) => {
+                put_u64(bytes, a.asset);
+                put_u64(bytes, a.quantity_qnt);
+            }
+            B::DistributeToHolders(a) => {
+                put_u64(bytes, a.asset);
+                put_u64(bytes, a.minimum_asset_quantity_qnt);
+                put_u64(bytes, a.asset_to_distribute);
+                put_u64(bytes, a.quantity_qnt);
+            }
+            B::AssetMultiTransfer(a) => {
+                bytes.push(a.asset_ids.len() as u8);
+                for (asset, quantity_qnt) in a.asset_ids.iter().zip(&a.quantities_qnt) {
+                    put_u64(bytes, *asset);
+                    put_u64(bytes, *quantity_qnt);
+                }
+            }
+            B::DigitalGoodsListing(a) => {
+                put_short_string(bytes, &a.name);
+                put_short_string(bytes, &a.description);
+                put_short_string(bytes, &a.tags);
+                bytes.extend_from_slice(&a.quantity.to_le_bytes());
+                put_u64(bytes, a.price_nqt);
+            }
+            B::DigitalGoodsDelisting(a) => put_u64(bytes, a.goods),
+            B::DigitalGoodsPriceChange(a) => {
+                put_u64(bytes, a.goods);
+                put_u64(bytes, a.price_nqt);
+            }
+            B::DigitalGoodsQuantityChange(a) => {
+                put_u64(bytes, a.goods);
+                bytes.extend_from_slice(&a.delta_quantity.to_le_bytes());
+            }
+            B::DigitalGoodsPurchase(a) => {
+                put_u64(bytes, a.goods);
+                bytes.extend_from_slice(&a.quantity.to_le_bytes());
+                put_u64(bytes, a.price_nqt);
+                bytes.extend_from_slice(&a.delivery_deadline_timestamp.to_le_bytes());
+            }
+            B::DigitalGoodsDelivery(a) => {
+                put_u64(bytes, a.purchase);
+                put_data_length(bytes, a.goods_data.len(), a.goods_is_text);
+                bytes.extend_from_slice(&a.goods_data);
+                bytes.extend_from_slice(&a.goods_nonce);
+                put_u64(bytes, a.discount_nqt);
+            }
+            B::DigitalGoodsFeedback(a) => put_u64(bytes, a.purchase),
+            B::DigitalGoodsRefund(a) => {
+                put_u64(bytes, a.purchase);
+                put_u64(bytes, a.refund_nqt);
+            }
+            B::EffectiveBalanceLeasing(a) => bytes.extend_from_slice(&a.period.to_le_bytes()),
+            B::CommitmentAdd(a) | B::CommitmentRemove(a) => put_u64(bytes, a.amount_nqt),
+            B::EscrowCreation(a) => {
+                put_u64(bytes, a.amount_nqt);
+                bytes.extend_from_slice(&a.deadline.to_le_bytes());
+                bytes.push(a.deadline_action as u8);
+                bytes.push(a.required_signers);
+                bytes.push(a.signers.len() as u8);
+                for signer in &a.signers {
+                    put_u64(bytes, *signer);
+                }
+            }
+            B::EscrowSign(a) | B::EscrowResult(a) => {
+                put_u64(bytes, a.escrow_id);
+                bytes.push(a.decision as u8);
+            }
+            B::SubscriptionSubscribe(a) => bytes.extend_from_slice(&a.frequency.to_le_bytes()),
+            B::SubscriptionCancel(a) | B::SubscriptionPayment(a) => {
+                put_u64(bytes, a.subscription_id)
+            }
+            B::AutomatedTransactionsCreation(a) => {
+                put_byte_string(bytes, &a.name);
+                put_short_string(bytes, &a.description);
+                bytes.extend_from_slice(&a.creation_bytes);
+            }
+        }
+    }
+}
+
+impl AttachmentBody {
+    /// Returns the name used for the attachment's `version.<Name>` field, if the attachment
+    /// has one.
+    fn name(&self) -> Option<&'static str> {
+        use AttachmentBody as B;
+
+        let name = match self {
+            B::OrdinaryPayment | B::ArbitraryMessage | B::AutomatedTransactionsPayment => {
+                return None
+            }
+            B::MultiOut(_) => "MultiOutCreation",
+            B::MultiSameOut(_) => "MultiSameOutCreation",
+            B::AliasAssignment(_) => "AliasAssignment",
+            B::AccountInfo(_) => "AccountInfo",
+            B::AliasSell(_) => "AliasSell",
+            B::AliasBuy(_) => "AliasBuy",
+            B::AssetIssuance(_) => "AssetIssuance",
+            B::AssetTransfer(_) => "AssetTransfer",
+            B::AskOrderPlacement(_) => "AskOrderPlacement",
+            B::BidOrderPlacement(_) => "BidOrderPlacement",
+            B::AskOrderCancellation(_) => "AskOrderCancellation",
+            B::BidOrderCancellation(_) => "BidOrderCancellation",
+            B::AssetMint(_) => "AssetMint",
+            B::AddTreasuryAccount => "AddTreasuryAccount",
+            B::DistributeToHolders(_) => "DistributeToHolders",
+            B::AssetMultiTransfer(_) => "AssetMultiTransfer",
+            B::DigitalGoodsListing(_) => "DigitalGoodsListing",
+            B::DigitalGoodsDelisting(_) => "DigitalGoodsDelisting",
+            B::DigitalGoodsPriceChange(_) => "DigitalGoodsPriceChange",
+            B::DigitalGoodsQuantityChange(_) => "DigitalGoodsQuantityChange",
+            B::DigitalGoodsPurchase(_) => "DigitalGoodsPurchase",
+            B::DigitalGoodsDelivery(_) => "DigitalGoodsDelivery",
+            B::DigitalGoodsFeedback(_) => "DigitalGoodsFeedback",
+            B::DigitalGoodsRefund(_) => "DigitalGoodsRefund",
+            B::EffectiveBalanceLeasing(_) => "EffectiveBalanceLeasing",
+            B::RewardRecipientAssignment => "RewardRecipientAssignment",
+            B::CommitmentAdd(_) => "CommitmentAdd",
+            B::CommitmentRemove(_) => "CommitmentRemove",
+            B::EscrowCreation(_) => "EscrowCreation",
+            B::EscrowSign(_) => "EscrowSign",
+            B::EscrowResult(_) => "EscrowResult",
+            B::SubscriptionSubscribe(_) => "SubscriptionSubscribe",
+            B::SubscriptionCancel(_) => "SubscriptionCancel",
+            B::SubscriptionPayment(_) => "SubscriptionPayment",
+            B::AutomatedTransactionsCreation(_) => "AutomatedTransactionsCreation",
+        };
+        Some(name)
+    }
+}
+
+/// Pays different amounts to up to 64 recipients.
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct MultiOut {
+    /// Pairs of recipient and amount in NQT.
+    #[serde_as(as = "Vec<(DisplayFromStr, DisplayFromStr)>")]
+    pub recipients: Vec<(u64, u64)>,
+}
+
+/// Pays the same amount to up to 128 recipients.
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct MultiSameOut {
+    #[serde_as(as = "Vec<DisplayFromStr>")]
+    pub recipients: Vec<u64>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct AliasAssignment {
+    pub alias: String,
+    pub uri: String,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct AccountInfo {
+    pub name: String,
+    pub description: String,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AliasSell {
+    pub alias: String,
+    #[serde(rename = "priceNQT")]
+    #[serde_as(as = "PickFirst<(_, DisplayFromStr)>")]
+    pub price_nqt: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct AliasBuy {
+    pub alias: String,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetIssuance {
+    pub name: String,
+    pub description: String,
+    #[serde(rename = "quantityQNT")]
+    #[serde_as(as = "PickFirst<(_, DisplayFromStr)>")]
+    pub quantity_qnt: u64,
+    pub decimals: u8,
+    /// Only part of version 2 attachments and later.
+    #[serde(default)]
+    pub mintable: bool,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetTransfer {
+    #[serde_as(as = "PickFirst<(DisplayFromStr, _)>")]
+    pub asset: u64,
+    #[serde(rename = "quantityQNT")]
+    #[serde_as(as = "PickFirst<(_, DisplayFromStr)>")]
+    pub quantity_qnt: u64,
+    /// Only part of version 0 attachments.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderPlacement {
+    #[serde_as(as = "PickFirst<(DisplayFromStr, _)>")]
+    pub asset: u64,
+    #[serde(rename = "quantityQNT")]
+    #[serde_as(as = "PickFirst<(_, DisplayFromStr)>")]
+    pub quantity_qnt: u64,
+    #[serde(rename = "priceNQT")]
+    #[serde_as(as = "PickFirst<(_, DisplayFromStr)>")]
+    pub price_nqt: u64,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct OrderCancellation {
+    #[serde_as(as = "PickFirst<(DisplayFromStr, _)>")]
+    pub order: u64,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetMint {
+    #[serde_as(as = "PickFirst<(DisplayFromStr, _)>")]
+    pub asset: u64,
+    #[serde(rename = "quantityQNT")]
+    #[serde_as(as = "PickFirst<(_, DisplayFromStr)>")]
+    pub quantity_qnt: u64,
+}
+
+/// Distributes an amount of Signa and optionally of another asset to the holders of an asset.
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DistributeToHolders {
+    #[serde_as(as = "PickFirst<(DisplayFromStr, _)>")]
+    pub asset: u64,
+    #[serde(rename = "minimumAssetQuantityQNT")]
+    #[serde_as(as = "PickFirst<(_, DisplayFromStr)>")]
+    pub minimum_asset_quantity_qnt: u64,
+    #[serde_as(as = "PickFirst<(DisplayFromStr, _)>")]
+    pub asset_to_distribute: u64,
+    #[serde(rename = "quantityQNT")]
+    #[serde_as(as = "PickFirst<(_, DisplayFromStr)>")]
+    pub quantity_qnt: u64,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetMultiTransfer {
+    #[serde_as(as = "Vec<PickFirst<(DisplayFromStr, _)>>")]
+    pub asset_ids: Vec<u64>,
+    #[serde(rename = "quantitiesQNT")]
+    #[serde_as(as = "Vec<PickFirst<(_, DisplayFromStr)>>")]
+    pub quantities_qnt: Vec<u64>,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DigitalGoodsListing {
+    pub name: String,
+    pub description: String,
+    pub tags: String,
+    pub quantity: i32,
+    #[serde(rename = "priceNQT")]
+    #[serde_as(as = "PickFirst<(_, DisplayFromStr)>")]
+    pub price_nqt: u64,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct DigitalGoodsReference {
+    #[serde_as(as = "PickFirst<(DisplayFromStr, _)>")]
+    pub goods: u64,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DigitalGoodsPriceChange {
+    #[serde_as(as = "PickFirst<(DisplayFromStr, _)>")]
+    pub goods: u64,
+    #[serde(rename = "priceNQT")]
+    #[serde_as(as = "PickFirst<(_, DisplayFromStr)>")]
+    pub price_nqt: u64,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DigitalGoodsQuantityChange {
+    #[serde_as(as = "PickFirst<(DisplayFromStr, _)>")]
+    pub goods: u64,
+    pub delta_quantity: i32,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DigitalGoodsPurchase {
+    #[serde_as(as = "PickFirst<(DisplayFromStr, _)>")]
+    pub goods: u64,
+    pub quantity: i32,
+    #[serde(rename = "priceNQT")]
+    #[serde_as(as = "PickFirst<(_, DisplayFromStr)>")]
+    pub price_nqt: u64,
+    pub delivery_deadline_timestamp: u32,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DigitalGoodsDelivery {
+    #[serde_as(as = "PickFirst<(DisplayFromStr, _)>")]
+    pub purchase: u64,
+    /// The goods, encrypted for the buyer.
+    #[serde_as(as = "Hex")]
+    pub goods_data: Vec<u8>,
+    #[serde_as(as = "Hex")]
+    pub goods_nonce: [u8; 32],
+    pub goods_is_text: bool,
+    #[serde(rename = "discountNQT")]
+    #[serde_as(as = "PickFirst<(_, DisplayFromStr)>")]
+    pub discount_nqt: u64,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PurchaseReference {
+    #[serde_as(as = "PickFirst<(DisplayFromStr, _)>")]
+    pub purchase: u64,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DigitalGoodsRefund {
+    #[serde_as(as = "PickFirst<(DisplayFromStr, _)>")]
+    pub purchase: u64,
+    #[serde(rename = "refundNQT")]
+    #[serde_as(as = "PickFirst<(_, DisplayFromStr)>")]
+    pub refund_nqt: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct EffectiveBalanceLeasing {
+    /// The number of blocks the balance is leased for.
+    pub period: u16,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Commitment {
+    #[serde(rename = "amountNQT")]
+    #[serde_as(as = "PickFirst<(_, DisplayFromStr)>")]
+    pub amount_nqt: u64,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EscrowCreation {
+    #[serde(rename = "amountNQT")]
+    #[serde_as(as = "PickFirst<(_, DisplayFromStr)>")]
+    pub amount_nqt: u64,
+    /// Seconds after the transaction's timestamp when the deadline action is taken.
+    pub deadline: u32,
+    pub deadline_action: EscrowDecisionKind,
+    pub required_signers: u8,
+    #[serde_as(as = "Vec<DisplayFromStr>")]
+    pub signers: Vec<u64>,
+}
+
+/// The outcome an escrow signer votes for, or that is taken when the escrow's deadline passes.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EscrowDecisionKind {
+    Undecided = 0,
+    Release = 1,
+    Refund = 2,
+    Split = 3,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EscrowDecision {
+    #[serde_as(as = "PickFirst<(DisplayFromStr, _)>")]
+    pub escrow_id: u64,
+    pub decision: EscrowDecisionKind,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SubscriptionSubscribe {
+    /// Seconds between payments.
+    pub frequency: u32,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionReference {
+    #[serde_as(as = "PickFirst<(DisplayFromStr, _)>")]
+    pub subscription_id: u64,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutomatedTransactionsCreation {
+    pub name: String,
+    pub description: String,
+    /// The AT's header, code and data, which carry their own lengths.
+    #[serde_as(as = "Hex")]
+    pub creation_bytes: Vec<u8>,
+}
+
+/// The optional parts a transaction of any type can carry next to its attachment, flagged in
+/// the transaction's bytes.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Appendages {
+    pub message: Option<Versioned<Message>>,
+    pub encrypted_message: Option<Versioned<EncryptedData>>,
+    pub public_key_announcement: Option<Versioned<[u8; 32]>>,
+    pub encrypt_to_self_message: Option<Versioned<EncryptedData>>,
+}
+
+/// An appendage along with the version it was created with.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Versioned<T> {
+    pub version: u8,
+    pub value: T,
+}
+
+impl Appendages {
+    const MESSAGE: &'static str = "Message";
+    const ENCRYPTED_MESSAGE: &'static str = "EncryptedMessage";
+    const PUBLIC_KEY_ANNOUNCEMENT: &'static str = "PublicKeyAnnouncement";
+    const ENCRYPT_TO_SELF_MESSAGE: &'static str = "EncryptToSelfMessage";
+
+    /// Reads the appendages present in a JSON `attachment` object.
+    pub fn from_json(json: &Map<String, Value>) -> Result<Self, AttachmentError> {
+        let message = match json.contains_key("message") {
+            true => Some(Versioned {
+                version: read_version(json, Self::MESSAGE)?,
+                value: parse::<Message>(json)?,
+            }),
+            false => None,
+        };
+        let encrypted_message = json
+            .get("encryptedMessage")
+            .map(|data| -> Result<_, AttachmentError> {
+                Ok(Versioned {
+                    version: read_version(json, Self::ENCRYPTED_MESSAGE)?,
+                    value: serde_json::from_value(data.clone())?,
+                })
+            })
+            .transpose()?;
+        let public_key_announcement = json
+            .get("recipientPublicKey")
+            .map(|key| -> Result<_, AttachmentError> {
+                let key = key.as_str().ok_or(AttachmentError::InvalidField {
+                    field: "recipientPublicKey",
+                })?;
+                let key = hex::decode(key)
+                    .ok()
+                    .and_then(|k| k.try_into().ok())
+                    .ok_or(AttachmentError::InvalidField {
+                        field: "recipientPublicKey",
+                    })?;
+                Ok(Versioned {
+                    version: read_version(json, Self::PUBLIC_KEY_ANNOUNCEMENT)?,
+                    value: key,
+                })
+            })
+            .transpose()?;
+        let encrypt_to_self_message = json
+            .get("encryptToSelfMessage")
+            .map(|data| -> Result<_, AttachmentError> {
+                Ok(Versioned {
+                    version: read_version(json, Self::ENCRYPT_TO_SELF_MESSAGE)?,
+                    value: serde_json::from_value(data.clone())?,
+                })
+            })
+            .transpose()?;
+
+        Ok(Self {
+            message,
+            encrypted_message,
+            public_key_announcement,
+            encrypt_to_self_message,
+        })
+    }
+
+    /// Writes the appendages into a JSON `attachment` object.
+    pub fn to_json(&self, json: &mut Map<String, Value>) -> Result<(), AttachmentError> {
+        if let Some(message) = &self.message {
+            if let Value::Object(fields) = serde_json::to_value(&message.value)? {
+                json.extend(fields);
+            }
+            write_version(json, Self::MESSAGE, message.version);
+        }
+        if let Some(message) = &self.encrypted_message {
+            json.insert(
+                "encryptedMessage".to_string(),
+                serde_json::to_value(&message.value)?,
+            );
+            write_version(json, Self::ENCRYPTED_MESSAGE, message.version);
+        }
+        if let Some(key) = &self.public_key_announcement {
+            json.insert(
+                "recipientPublicKey".to_string(),
+                Value::String(hex::encode(key.value)),
+            );
+            write_version(json, Self::PUBLIC_KEY_ANNOUNCEMENT, key.version);
+        }
+        if let Some(message) = &self.encrypt_to_self_message {
+            json.insert(
+                "encryptToSelfMessage".to_string(),
+                serde_json::to_value(&message.value)?,
+            );
+            write_version(json, Self::ENCRYPT_TO_SELF_MESSAGE, message.version);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the flags announcing which appendages follow the attachment in the
+    /// transaction's bytes.
+    pub fn flags(&self) -> u32 {
+        let mut flags = 0;
+        if self.message.is_some() {
+            flags |= 1;
+        }
+        if self.encrypted_message.is_some() {
+            flags |= 1 << 1;
+        }
+        if self.public_key_announcement.is_some() {
+            flags |= 1 << 2;
+        }
+        if self.encrypt_to_self_message.is_some() {
+            flags |= 1 << 3;
+        }
+        flags
+    }
+
+    /// Appends the binary form of the appendages to `bytes`, in the order of their flags.
+    pub fn put_bytes(&self, bytes: &mut Vec<u8>) {
+        if let Some(message) = &self.message {
+            put_version(bytes, message.version);
+            put_data_length(bytes, message.value.data.len(), message.value.is_text);
+            bytes.extend_from_slice(&message.value.data);
+        }
+        if let Some(message) = &self.encrypted_message {
+            put_version(bytes, message.version);
+            message.value.put_bytes(bytes);
+        }
+        if let Some(key) = &self.public_key_announcement {
+            put_version(bytes, key.version);
+            bytes.extend_from_slice(&key.value);
+        }
+        if let Some(message) = &self.encrypt_to_self_message {
+            put_version(bytes, message.version);
+            message.value.put_bytes(bytes);
+        }
+    }
+}
+
+/// A plain message, which is sent as text or as hex encoded binary data.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(try_from = "MessageJson", into = "MessageJson")]
+pub struct Message {
+    pub data: Vec<u8>,
+    pub is_text: bool,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MessageJson {
+    message: String,
+    message_is_text: bool,
+}
+
+impl TryFrom<MessageJson> for Message {
+    type Error = hex::FromHexError;
+
+    fn try_from(value: MessageJson) -> Result<Self, Self::Error> {
+        let data = match value.message_is_text {
+            true => value.message.into_bytes(),
+            false => hex::decode(value.message)?,
+        };
+        Ok(Self {
+            data,
+            is_text: value.message_is_text,
+        })
+    }
+}
+
+impl From<Message> for MessageJson {
+    fn from(value: Message) -> Self {
+        let message = match value.is_text {
+            true => String::from_utf8_lossy(&value.data).into_owned(),
+            false => hex::encode(&value.data),
+        };
+        Self {
+            message,
+            message_is_text: value.is_text,
+        }
+    }
+}
+
+/// A message encrypted for the recipient or for the sender.
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptedData {
+    #[serde_as(as = "Hex")]
+    pub data: Vec<u8>,
+    #[serde_as(as = "Hex")]
+    pub nonce: [u8; 32],
+    pub is_text: bool,
+}
+
+impl EncryptedData {
+    fn put_bytes(&self, bytes: &mut Vec<u8>) {
+        put_data_length(bytes, self.data.len(), self.is_text);
+        bytes.extend_from_slice(&self.data);
+        bytes.extend_from_slice(&self.nonce);
+    }
+}
+
+fn parse<T: DeserializeOwned>(json: &Map<String, Value>) -> Result<T, AttachmentError> {
+    Ok(serde_json::from_value(Value::Object(json.clone()))?)
+}
+
+/// Reads the `version.<name>` field, which is missing for version 0.
+fn read_version(json: &Map<String, Value>, name: &'static str) -> Result<u8, AttachmentError> {
+    match json.get(&format!("version.{}", name)) {
+        None => Ok(0),
+        Some(version) => version
+            .as_u64()
+            .and_then(|v| u8::try_from(v).ok())
+            .ok_or(AttachmentError::InvalidField { field: name }),
+    }
+}
+
+fn write_version(json: &mut Map<String, Value>, name: &str, version: u8) {
+    if version > 0 {
+        json.insert(format!("version.{}", name), Value::from(version));
+    }
+}
+
+fn put_version(bytes: &mut Vec<u8>, version: u8) {
+    if version > 0 {
+        bytes.push(version);
+    }
+}
+
+fn put_u64(bytes: &mut Vec<u8>, value: u64) {
+    bytes.extend_from_slice(&value.to_le_bytes());
+}
+
+// String and data lengths are bounded well below their prefixes by the protocol's limits.
+
+/// Appends a string prefixed by its length as one byte.
+fn put_byte_string(bytes: &mut Vec<u8>, value: &str) {
+    bytes.push(value.len() as u8);
+    bytes.extend_from_slice(value.as_bytes());
+}
+
+/// Appends a string prefixed by its length as two bytes.
+fn put_short_string(bytes: &mut Vec<u8>, value: &str) {
+    bytes.extend_from_slice(&(value.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(value.as_bytes());
+}
+
+/// Appends the length of message data, with the top bit set for text.
+fn put_data_length(bytes: &mut Vec<u8>, length: usize, is_text: bool) {
+    let mut length = length as u32;
+    if is_text {
+        length |= 1 << 31;
+    }
+    bytes.extend_from_slice(&length.to_le_bytes());
+}
+
+/// Represents the reasons an attachment can't be read.
+#[derive(thiserror::Error)]
+pub enum AttachmentError {
+    #[error("{field} is invalid")]
+    InvalidField { field: &'static str },
+    #[error("attachment is invalid: {0}")]
+    InvalidJson(#[from] serde_json::Error),
+}
+
+impl std::fmt::Debug for AttachmentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        crate::error_chain_fmt(self, f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::{json, Map, Value};
+
+    use crate::models::p2p::TransactionType;
+
+    use super::{Appendages, Attachment, AttachmentBody, EscrowDecisionKind};
+
+    fn object(value: Value) -> Map<String, Value> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn multi_out_round_trips_through_json() {
+        let json = object(json!({
+            "version.MultiOutCreation": 1,
+            "recipients": [["17997500163001214854", "2026541011"]]
+        }));
+
+        let attachment = Attachment::from_json(TransactionType::MultiOut, &json).unwrap();
+        let AttachmentBody::MultiOut(multi_out) = &attachment.body else {
+            panic!("expected a multi-out attachment, got {:?}", attachment.body);
+        };
+        assert_eq!(attachment.version, 1);
+        assert_eq!(
+            multi_out.recipients,
+            vec![(17_997_500_163_001_214_854, 2_026_541_011)]
+        );
+
+        let mut round_trip = Map::new();
+        attachment.to_json(&mut round_trip).unwrap();
+        assert_eq!(round_trip, json);
+    }
+
+    #[test]
+    fn escrow_sign_reads_decision_names() {
+        let json = object(json!({
+            "version.EscrowSign": 1,
+            "escrowId": "123",
+            "decision": "refund"
+        }));
+
+        let attachment = Attachment::from_json(TransactionType::EscrowSign, &json).unwrap();
+
+        let mut bytes = Vec::new();
+        attachment.put_bytes(&mut bytes);
+        assert!(matches!(
+            attachment.body,
+            AttachmentBody::EscrowSign(ref sign) if sign.decision == EscrowDecisionKind::Refund
+        ));
+        assert_eq!(bytes, [&[1][..], &123u64.to_le_bytes(), &[2]].concat());
+    }
+
+    #[test]
+    fn appendages_are_read_next_to_the_attachment() {
+        let json = object(json!({
+            "version.Message": 1,
+            "message": "00ff",
+            "messageIsText": false,
+            "version.PublicKeyAnnouncement": 1,
+            "recipientPublicKey": "11".repeat(32)
+        }));
+
+        let appendages = Appendages::from_json(&json).unwrap();
+
+        assert_eq!(appendages.flags(), 0b101);
+        assert_eq!(appendages.message.as_ref().unwrap().value.data, [0, 255]);
+        let mut round_trip = Map::new();
+        appendages.to_json(&mut round_trip).unwrap();
+        assert_eq!(round_trip, json);
+    }
+
+    #[test]
+    fn from_json_rejects_missing_fields() {
+        let json = object(json!({ "version.AssetTransfer": 1, "asset": "1" }));
+
+        assert!(Attachment::from_json(TransactionType::AssetTransfer, &json).is_err());
+    }
+}
@@ -3,50 +3,52 @@ use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DisplayFromStr};
 
 #[serde_as]
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct B1Block {
-    version: u8,
+    /// The genesis block is version -1.
+    pub version: i32,
 
     /// Some number since Signum's start epoch
-    timestamp: u64,
+    pub timestamp: u64,
 
     #[serde_as(as = "DisplayFromStr")]
-    previous_block: u64,
+    pub previous_block: u64,
 
     #[serde(rename = "totalAmountNQT")]
-    total_amount_nqt: u64,
+    pub total_amount_nqt: u64,
 
     #[serde(rename = "totalFeeNQT")]
-    total_fee_nqt: u64,
+    pub total_fee_nqt: u64,
 
     #[serde(rename = "totalFeeCashBackNQT")]
-    total_fee_cashback_nqt: u64,
+    pub total_fee_cashback_nqt: u64,
 
     #[serde(rename = "totalFeeBurntNQT")]
-    total_fee_burnt_nqt: u64,
+    pub total_fee_burnt_nqt: u64,
 
-    payload_length: u32,
+    pub payload_length: u32,
 
-    payload_hash: String,
+    pub payload_hash: String,
 
-    generator_public_key: String,
+    pub generator_public_key: String,
 
-    generation_signature: String,
+    pub generation_signature: String,
 
     /// `previous_block_hash` is only valid in v1 blocks.
-    previous_block_hash: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_block_hash: Option<String>,
 
-    block_signature: String,
+    pub block_signature: String,
 
-    transactions: Vec<B1Transaction>,
+    pub transactions: Vec<B1Transaction>,
 
     #[serde_as(as = "DisplayFromStr")]
-    nonce: u64,
+    pub nonce: u64,
 
     #[serde_as(as = "DisplayFromStr")]
-    base_target: u64,
+    pub base_target: u64,
 
-    #[serde(rename = "blockATs")]
-    block_ats: Option<String>,
+    #[serde(rename = "blockATs", default, skip_serializing_if = "Option::is_none")]
+    pub block_ats: Option<String>,
 }
@@ -1,8 +1,8 @@
 use serde::{Deserialize, Serialize};
-use serde_with::{serde_as, DisplayFromStr};
+use serde_with::{serde_as, DisplayFromStr, PickFirst};
 
 #[serde_as]
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct B1Transaction {
     #[serde(rename = "type")]
@@ -12,12 +12,16 @@ pub struct B1Transaction {
     pub deadline: u16,
     pub sender_public_key: String,
     #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub recipient: Option<u64>, // TODO Why is this an Option? Are transaction recipients optional?
     #[serde(rename = "amountNQT")]
+    #[serde_as(as = "PickFirst<(_, DisplayFromStr)>")]
     pub amount_nqt: u64,
     #[serde(rename = "feeNQT")]
+    #[serde_as(as = "PickFirst<(_, DisplayFromStr)>")]
     pub fee_nqt: u64,
-    //pub referenced_transaction_full_hash: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub referenced_transaction_full_hash: Option<String>,
     #[serde(rename = "ecBlockHeight")]
     pub ec_block_height: u32,
     #[serde(rename = "ecBlockId")]
@@ -27,14 +31,17 @@ pub struct B1Transaction {
     #[serde_as(as = "DisplayFromStr")]
     pub cash_back_id: u64,
     pub signature: String,
-    //pub attachment: Vec<B1TransactionAttachment>,
+    /// The transaction's attachment and appendages, decoded by [`super::Attachment`] and
+    /// [`super::Appendages`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attachment: Option<serde_json::Map<String, serde_json::Value>>,
     pub version: u8,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
-pub enum B1TransactionAttachment {
-    Message,
-    EncryptedMessage,
-    EncryptMessageToSelf,
-    PublicKeyAnnouncement,
+impl B1Transaction {
+    /// Returns the signum timestamp after which this transaction can no longer be included in
+    /// a block.
+    pub fn expiration(&self) -> u64 {
+        self.timestamp + u64::from(self.deadline) * 60
+    }
 }
@@ -1,2 +1,40 @@
-#[derive(Debug)]
-pub struct BlockId;
+use std::{fmt::Display, num::ParseIntError, str::FromStr};
+
+/// A block's ID: the first 8 bytes of its full hash, read as a little-endian number.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct BlockId(u64);
+
+impl BlockId {
+    /// Derives the ID of the block with the given full hash.
+    pub fn from_full_hash(full_hash: &[u8; 32]) -> Self {
+        let mut id = [0u8; 8];
+        id.copy_from_slice(&full_hash[..8]);
+        Self(u64::from_le_bytes(id))
+    }
+}
+
+impl From<u64> for BlockId {
+    fn from(value: u64) -> Self {
+        Self(value)
+    }
+}
+
+impl From<BlockId> for u64 {
+    fn from(value: BlockId) -> Self {
+        value.0
+    }
+}
+
+impl Display for BlockId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for BlockId {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(u64::from_str(s)?))
+    }
+}
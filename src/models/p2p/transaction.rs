@@ -1,2 +1,304 @@
-#[derive(Debug)]
-pub struct Transaction;
+use sha2::{Digest, Sha256};
+
+use crate::{crypto, models::Amount};
+
+use super::{
+    Appendages, Attachment, AttachmentError, B1Transaction, TransactionId, TransactionType,
+    UnknownTransactionType,
+};
+
+/// A transaction with its fields decoded from the hex strings used on the wire.
+#[derive(Clone, Debug)]
+pub struct Transaction {
+    pub id: TransactionId,
+    pub full_hash: [u8; 32],
+    pub transaction_type: TransactionType,
+    pub version: u8,
+    pub timestamp: u64,
+    /// How many minutes after `timestamp` the transaction may still be included in a block.
+    pub deadline: u16,
+    pub sender_public_key: [u8; 32],
+    pub recipient: Option<u64>,
+    pub amount: Amount,
+    pub fee: Amount,
+    pub referenced_transaction_full_hash: Option<[u8; 32]>,
+    pub signature: [u8; 64],
+    pub ec_block_height: u32,
+    pub ec_block_id: u64,
+    pub cash_back_id: u64,
+    pub attachment: Attachment,
+    pub appendages: Appendages,
+}
+
+impl TryFrom<B1Transaction> for Transaction {
+    type Error = TransactionConversionError;
+
+    fn try_from(value: B1Transaction) -> Result<Self, Self::Error> {
+        let transaction_type = TransactionType::try_from((value.transaction_type, value.subtype))?;
+        let attachment_json = value.attachment.unwrap_or_default();
+        let attachment = Attachment::from_json(transaction_type, &attachment_json)?;
+        let appendages = Appendages::from_json(&attachment_json)?;
+
+        let referenced_transaction_full_hash = value
+            .referenced_transaction_full_hash
+            .as_ref()
+            .map(|hash| decode_fixed("referencedTransactionFullHash", hash))
+            .transpose()?;
+
+        let mut transaction = Self {
+            id: TransactionId::default(),
+            full_hash: [0; 32],
+            transaction_type,
+            version: value.version,
+            timestamp: value.timestamp,
+            deadline: value.deadline,
+            sender_public_key: decode_fixed("senderPublicKey", &value.sender_public_key)?,
+            recipient: value.recipient,
+            amount: Amount::from_nqt(value.amount_nqt),
+            fee: Amount::from_nqt(value.fee_nqt),
+            referenced_transaction_full_hash,
+            signature: decode_fixed("signature", &value.signature)?,
+            ec_block_height: value.ec_block_height,
+            ec_block_id: value.ec_block_id,
+            cash_back_id: value.cash_back_id,
+            attachment,
+            appendages,
+        };
+        transaction.full_hash = transaction.calculate_full_hash();
+        transaction.id = TransactionId::from_full_hash(&transaction.full_hash);
+
+        Ok(transaction)
+    }
+}
+
+impl Transaction {
+    /// Returns the transaction in the binary layout BRS uses for hashing, signing and block
+    /// payloads.
+    ///
+    /// Version 1 added the appendage flags and economic clustering fields, and version 2
+    /// added the cash back ID. The attachment and appendages follow the fixed fields.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.bytes_with_signature(&self.signature)
+    }
+
+    /// Returns the bytes the sender signs, which are the transaction's bytes with the
+    /// signature zeroed.
+    pub fn unsigned_bytes(&self) -> Vec<u8> {
+        self.bytes_with_signature(&[0; 64])
+    }
+
+    fn bytes_with_signature(&self, signature: &[u8; 64]) -> Vec<u8> {
+        let (transaction_type, subtype) = self.transaction_type.type_and_subtype();
+        let mut bytes = Vec::with_capacity(184);
+        bytes.push(transaction_type);
+        bytes.push((self.version << 4) | subtype);
+        bytes.extend_from_slice(&(self.timestamp as u32).to_le_bytes());
+        bytes.extend_from_slice(&self.deadline.to_le_bytes());
+        bytes.extend_from_slice(&self.sender_public_key);
+        // Transactions without a recipient name the genesis creator, account 0
+        bytes.extend_from_slice(&self.recipient.unwrap_or_default().to_le_bytes());
+        bytes.extend_from_slice(&self.amount.nqt().to_le_bytes());
+        bytes.extend_from_slice(&self.fee.nqt().to_le_bytes());
+        bytes.extend_from_slice(&self.referenced_transaction_full_hash.unwrap_or_default());
+        bytes.extend_from_slice(signature);
+        if self.version > 0 {
+            bytes.extend_from_slice(&self.appendages.flags().to_le_bytes());
+            bytes.extend_from_slice(&self.ec_block_height.to_le_bytes());
+            bytes.extend_from_slice(&self.ec_block_id.to_le_bytes());
+        }
+        if self.version > 1 {
+            bytes.extend_from_slice(&self.cash_back_id.to_le_bytes());
+        }
+        self.attachment.put_bytes(&mut bytes);
+        self.appendages.put_bytes(&mut bytes);
+
+        bytes
+    }
+
+    /// Returns whether the transaction is signed by its sender.
+    pub fn verify_signature(&self) -> bool {
+        crypto::verify(
+            &self.signature,
+            &self.unsigned_bytes(),
+            &self.sender_public_key,
+            true,
+        )
+    }
+
+    /// Returns the hash identifying the transaction, which covers the unsigned bytes and the
+    /// hash of the signature.
+    fn calculate_full_hash(&self) -> [u8; 32] {
+        let signature_hash = Sha256::digest(self.signature);
+        Sha256::new()
+            .chain_update(self.unsigned_bytes())
+            .chain_update(signature_hash)
+            .finalize()
+            .into()
+    }
+}
+
+/// Decodes a hex string that must hold exactly `N` bytes.
+fn decode_fixed<const N: usize>(
+    field: &'static str,
+    value: &str,
+) -> Result<[u8; N], TransactionConversionError> {
+    let bytes = hex::decode(value)
+        .map_err(|source| TransactionConversionError::InvalidHex { field, source })?;
+    bytes
+        .try_into()
+        .map_err(|bytes: Vec<u8>| TransactionConversionError::InvalidLength {
+            field,
+            expected: N,
+            actual: bytes.len(),
+        })
+}
+
+/// Represents the reasons a [`B1Transaction`] can't be converted into a [`Transaction`].
+#[derive(thiserror::Error)]
+pub enum TransactionConversionError {
+    #[error("{field} is not valid hex")]
+    InvalidHex {
+        field: &'static str,
+        #[source]
+        source: hex::FromHexError,
+    },
+    #[error("{field} should be {expected} bytes but was {actual}")]
+    InvalidLength {
+        field: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+    #[error(transparent)]
+    UnknownTransactionType(#[from] UnknownTransactionType),
+    #[error("attachment is invalid")]
+    InvalidAttachment(#[from] AttachmentError),
+}
+
+impl std::fmt::Debug for TransactionConversionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        crate::error_chain_fmt(self, f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::models::{
+        p2p::{B1Transaction, TransactionId},
+        Amount,
+    };
+
+    use super::{Transaction, TransactionConversionError};
+
+    /// An ordinary payment from mainnet.
+    fn b1_transaction() -> B1Transaction {
+        serde_json::from_value(serde_json::json!({
+            "type": 0,
+            "subtype": 0,
+            "timestamp": 298416440,
+            "deadline": 60,
+            "senderPublicKey": "7347ae2ec9aaa0a93f90a1c394c370a629227928ba2f24843634c81358d2b403",
+            "recipient": "13657951110994294056",
+            "amountNQT": "1000000",
+            "feeNQT": "1000000",
+            "signature": "bc008447457a87c46fcecfe7bcfba4686581d42ba5dae53568983c3411339e08c02222692c561fcc40e8c033ddc0d29fe0308d07570f12facad7f2b3ce895b75",
+            "version": 2,
+            "ecBlockId": "6079409147758822541",
+            "ecBlockHeight": 1237188,
+            "cashBackId": "13420738867631717395"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn transaction_has_mainnet_id_and_full_hash() {
+        let transaction = Transaction::try_from(b1_transaction()).unwrap();
+
+        assert_eq!(
+            hex::encode(transaction.full_hash),
+            "13ca4b3e4cafa18b2e327b68af4bebd137ffd45591dfecf1b4e146a74d662688"
+        );
+        assert_eq!(
+            transaction.id,
+            TransactionId::from(10_061_515_784_519_928_339)
+        );
+    }
+
+    #[test]
+    fn mainnet_transaction_signature_verifies() {
+        let mut transaction = Transaction::try_from(b1_transaction()).unwrap();
+        assert!(transaction.verify_signature());
+
+        transaction.amount = Amount::from_nqt(transaction.amount.nqt() + 1);
+        assert!(!transaction.verify_signature());
+    }
+
+    #[test]
+    fn to_bytes_follows_version_layout() {
+        let mut transaction = Transaction::try_from(b1_transaction()).unwrap();
+
+        let bytes = transaction.to_bytes();
+        assert_eq!(bytes.len(), 184);
+        assert_eq!(bytes[1], 0x20);
+        assert_eq!(&bytes[96..160], &transaction.signature);
+        assert_eq!(&transaction.unsigned_bytes()[96..160], &[0; 64]);
+
+        transaction.version = 1;
+        assert_eq!(transaction.to_bytes().len(), 176);
+
+        transaction.version = 0;
+        assert_eq!(transaction.to_bytes().len(), 160);
+    }
+
+    #[test]
+    fn multi_out_attachment_is_encoded_after_fixed_fields() {
+        let mut b1_transaction = b1_transaction();
+        b1_transaction.subtype = 1;
+        b1_transaction.attachment = serde_json::from_value(serde_json::json!({
+            "version.MultiOutCreation": 1,
+            "recipients": [
+                ["17997500163001214854", "2026541011"],
+                ["12625359299301436821", "9405744078"]
+            ]
+        }))
+        .unwrap();
+
+        let transaction = Transaction::try_from(b1_transaction).unwrap();
+
+        let bytes = transaction.to_bytes();
+        assert_eq!(bytes.len(), 184 + 1 + 1 + 2 * 16);
+        assert_eq!(&bytes[184..186], &[1, 2]);
+        assert_eq!(
+            &bytes[186..194],
+            &17_997_500_163_001_214_854u64.to_le_bytes()
+        );
+        assert_eq!(&bytes[194..202], &2_026_541_011u64.to_le_bytes());
+    }
+
+    #[test]
+    fn message_appendage_sets_its_flag() {
+        let mut b1_transaction = b1_transaction();
+        b1_transaction.attachment = serde_json::from_value(serde_json::json!({
+            "version.Message": 1,
+            "message": "hi",
+            "messageIsText": true
+        }))
+        .unwrap();
+
+        let transaction = Transaction::try_from(b1_transaction).unwrap();
+
+        let bytes = transaction.to_bytes();
+        assert_eq!(&bytes[160..164], &1u32.to_le_bytes());
+        assert_eq!(&bytes[184..], &[1, 2, 0, 0, 0x80, b'h', b'i']);
+    }
+
+    #[test]
+    fn transaction_try_from_rejects_unknown_types() {
+        let mut b1_transaction = b1_transaction();
+        b1_transaction.transaction_type = 99;
+
+        assert!(matches!(
+            Transaction::try_from(b1_transaction),
+            Err(TransactionConversionError::UnknownTransactionType(_))
+        ));
+    }
+}
@@ -0,0 +1,247 @@
+use crate::{Active, HistoricalMoments};
+
+/// Every kind of transaction Signum knows, identified on the wire by a type and subtype byte.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum TransactionType {
+    OrdinaryPayment,
+    MultiOut,
+    MultiSameOut,
+    ArbitraryMessage,
+    AliasAssignment,
+    AccountInfo,
+    AliasSell,
+    AliasBuy,
+    AssetIssuance,
+    AssetTransfer,
+    AskOrderPlacement,
+    BidOrderPlacement,
+    AskOrderCancellation,
+    BidOrderCancellation,
+    AssetMint,
+    AddTreasuryAccount,
+    DistributeToHolders,
+    AssetMultiTransfer,
+    DigitalGoodsListing,
+    DigitalGoodsDelisting,
+    DigitalGoodsPriceChange,
+    DigitalGoodsQuantityChange,
+    DigitalGoodsPurchase,
+    DigitalGoodsDelivery,
+    DigitalGoodsFeedback,
+    DigitalGoodsRefund,
+    EffectiveBalanceLeasing,
+    RewardRecipientAssignment,
+    CommitmentAdd,
+    CommitmentRemove,
+    EscrowCreation,
+    EscrowSign,
+    EscrowResult,
+    SubscriptionSubscribe,
+    SubscriptionCancel,
+    SubscriptionPayment,
+    AutomatedTransactionsCreation,
+    AutomatedTransactionsPayment,
+}
+
+impl TransactionType {
+    /// Returns the type and subtype bytes identifying this kind of transaction.
+    pub fn type_and_subtype(&self) -> (u8, u8) {
+        use TransactionType as T;
+
+        match self {
+            T::OrdinaryPayment => (0, 0),
+            T::MultiOut => (0, 1),
+            T::MultiSameOut => (0, 2),
+            T::ArbitraryMessage => (1, 0),
+            T::AliasAssignment => (1, 1),
+            T::AccountInfo => (1, 5),
+            T::AliasSell => (1, 6),
+            T::AliasBuy => (1, 7),
+            T::AssetIssuance => (2, 0),
+            T::AssetTransfer => (2, 1),
+            T::AskOrderPlacement => (2, 2),
+            T::BidOrderPlacement => (2, 3),
+            T::AskOrderCancellation => (2, 4),
+            T::BidOrderCancellation => (2, 5),
+            T::AssetMint => (2, 6),
+            T::AddTreasuryAccount => (2, 7),
+            T::DistributeToHolders => (2, 8),
+            T::AssetMultiTransfer => (2, 9),
+            T::DigitalGoodsListing => (3, 0),
+            T::DigitalGoodsDelisting => (3, 1),
+            T::DigitalGoodsPriceChange => (3, 2),
+            T::DigitalGoodsQuantityChange => (3, 3),
+            T::DigitalGoodsPurchase => (3, 4),
+            T::DigitalGoodsDelivery => (3, 5),
+            T::DigitalGoodsFeedback => (3, 6),
+            T::DigitalGoodsRefund => (3, 7),
+            T::EffectiveBalanceLeasing => (4, 0),
+            T::RewardRecipientAssignment => (20, 0),
+            T::CommitmentAdd => (20, 1),
+            T::CommitmentRemove => (20, 2),
+            T::EscrowCreation => (21, 0),
+            T::EscrowSign => (21, 1),
+            T::EscrowResult => (21, 2),
+            T::SubscriptionSubscribe => (21, 3),
+            T::SubscriptionCancel => (21, 4),
+            T::SubscriptionPayment => (21, 5),
+            T::AutomatedTransactionsCreation => (22, 0),
+            T::AutomatedTransactionsPayment => (22, 1),
+        }
+    }
+
+    /// Returns the height from which transactions of this kind may be included in blocks.
+    pub fn enabled_from(&self, historical_moments: &HistoricalMoments<Active>) -> u32 {
+        use TransactionType as T;
+
+        match self {
+            T::OrdinaryPayment
+            | T::ArbitraryMessage
+            | T::AliasAssignment
+            | T::AccountInfo
+            | T::AssetIssuance
+            | T::AssetTransfer
+            | T::AskOrderPlacement
+            | T::BidOrderPlacement
+            | T::AskOrderCancellation
+            | T::BidOrderCancellation
+            | T::EffectiveBalanceLeasing => historical_moments.genesis(),
+            T::MultiOut | T::MultiSameOut => historical_moments.pre_poc2(),
+            T::AliasSell
+            | T::AliasBuy
+            | T::DigitalGoodsListing
+            | T::DigitalGoodsDelisting
+            | T::DigitalGoodsPriceChange
+            | T::DigitalGoodsQuantityChange
+            | T::DigitalGoodsPurchase
+            | T::DigitalGoodsDelivery
+            | T::DigitalGoodsFeedback
+            | T::DigitalGoodsRefund
+            | T::EscrowCreation
+            | T::EscrowSign
+            | T::EscrowResult
+            | T::SubscriptionSubscribe
+            | T::SubscriptionCancel
+            | T::SubscriptionPayment => historical_moments.digital_goods_store_enable(),
+            T::AssetMint
+            | T::AddTreasuryAccount
+            | T::DistributeToHolders
+            | T::AssetMultiTransfer => historical_moments.smart_token_enable(),
+            T::RewardRecipientAssignment => historical_moments.reward_recipient_enable(),
+            T::CommitmentAdd | T::CommitmentRemove => historical_moments.signum_name_change(),
+            T::AutomatedTransactionsCreation | T::AutomatedTransactionsPayment => {
+                historical_moments.automated_transaction_enable()
+            }
+        }
+    }
+
+    /// Returns whether transactions of this kind may be included in a block at `height`.
+    pub fn is_enabled_at(
+        &self,
+        height: u64,
+        historical_moments: &HistoricalMoments<Active>,
+    ) -> bool {
+        height >= u64::from(self.enabled_from(historical_moments))
+    }
+}
+
+impl TryFrom<(u8, u8)> for TransactionType {
+    type Error = UnknownTransactionType;
+
+    fn try_from((transaction_type, subtype): (u8, u8)) -> Result<Self, Self::Error> {
+        use TransactionType as T;
+
+        let transaction_type = match (transaction_type, subtype) {
+            (0, 0) => T::OrdinaryPayment,
+            (0, 1) => T::MultiOut,
+            (0, 2) => T::MultiSameOut,
+            (1, 0) => T::ArbitraryMessage,
+            (1, 1) => T::AliasAssignment,
+            (1, 5) => T::AccountInfo,
+            (1, 6) => T::AliasSell,
+            (1, 7) => T::AliasBuy,
+            (2, 0) => T::AssetIssuance,
+            (2, 1) => T::AssetTransfer,
+            (2, 2) => T::AskOrderPlacement,
+            (2, 3) => T::BidOrderPlacement,
+            (2, 4) => T::AskOrderCancellation,
+            (2, 5) => T::BidOrderCancellation,
+            (2, 6) => T::AssetMint,
+            (2, 7) => T::AddTreasuryAccount,
+            (2, 8) => T::DistributeToHolders,
+            (2, 9) => T::AssetMultiTransfer,
+            (3, 0) => T::DigitalGoodsListing,
+            (3, 1) => T::DigitalGoodsDelisting,
+            (3, 2) => T::DigitalGoodsPriceChange,
+            (3, 3) => T::DigitalGoodsQuantityChange,
+            (3, 4) => T::DigitalGoodsPurchase,
+            (3, 5) => T::DigitalGoodsDelivery,
+            (3, 6) => T::DigitalGoodsFeedback,
+            (3, 7) => T::DigitalGoodsRefund,
+            (4, 0) => T::EffectiveBalanceLeasing,
+            (20, 0) => T::RewardRecipientAssignment,
+            (20, 1) => T::CommitmentAdd,
+            (20, 2) => T::CommitmentRemove,
+            (21, 0) => T::EscrowCreation,
+            (21, 1) => T::EscrowSign,
+            (21, 2) => T::EscrowResult,
+            (21, 3) => T::SubscriptionSubscribe,
+            (21, 4) => T::SubscriptionCancel,
+            (21, 5) => T::SubscriptionPayment,
+            (22, 0) => T::AutomatedTransactionsCreation,
+            (22, 1) => T::AutomatedTransactionsPayment,
+            _ => {
+                return Err(UnknownTransactionType {
+                    transaction_type,
+                    subtype,
+                })
+            }
+        };
+        Ok(transaction_type)
+    }
+}
+
+/// A type and subtype pair that doesn't name any kind of transaction.
+#[derive(thiserror::Error)]
+#[error("unknown transaction type {transaction_type} with subtype {subtype}")]
+pub struct UnknownTransactionType {
+    pub transaction_type: u8,
+    pub subtype: u8,
+}
+
+impl std::fmt::Debug for UnknownTransactionType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        crate::error_chain_fmt(self, f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::HistoricalMoments;
+
+    use super::TransactionType;
+
+    #[test]
+    fn type_and_subtype_round_trip() {
+        for transaction_type in 0..=u8::MAX {
+            for subtype in 0..16 {
+                if let Ok(kind) = TransactionType::try_from((transaction_type, subtype)) {
+                    assert_eq!(kind.type_and_subtype(), (transaction_type, subtype));
+                }
+            }
+        }
+        assert!(TransactionType::try_from((1, 2)).is_err());
+    }
+
+    #[test]
+    fn token_transactions_are_enabled_from_smart_token_height() {
+        let historical_moments = HistoricalMoments::new().build();
+        let smart_token_enable = u64::from(historical_moments.smart_token_enable());
+
+        assert!(
+            !TransactionType::AssetMint.is_enabled_at(smart_token_enable - 1, &historical_moments)
+        );
+        assert!(TransactionType::AssetMint.is_enabled_at(smart_token_enable, &historical_moments));
+        assert!(TransactionType::OrdinaryPayment.is_enabled_at(0, &historical_moments));
+    }
+}
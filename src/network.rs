@@ -0,0 +1,109 @@
+//! The networks the node can join. Each preset decides the network name, bootstrap peers,
+//! default port and fork schedule together, so a single `node.network` setting switches
+//! between them. Any of them can still be overridden in the configuration file.
+//!
+//! There is no testnet preset yet. Its bootstrap peers, port, genesis block and fork heights
+//! have to be taken from the reference node's testnet configuration, which hasn't been done,
+//! and a guessed fork schedule would fork the node off the testnet chain.
+
+use serde::Deserialize;
+
+use crate::{Building, Fork, HistoricalMoments};
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Network {
+    /// The Signum main network. Older configurations name it after its network name.
+    #[default]
+    #[serde(alias = "Signum")]
+    Mainnet,
+    /// A private network on the local machine or LAN, with its own genesis block and every
+    /// fork active from the start.
+    Devnet,
+}
+
+impl Network {
+    /// The name peers exchange to make sure they're on the same network.
+    pub fn network_name(&self) -> &'static str {
+        match self {
+            Network::Mainnet => "Signum",
+            Network::Devnet => "Signum-DEVNET",
+        }
+    }
+
+    /// The peers to start from when the database doesn't know any yet.
+    pub fn bootstrap_peers(&self) -> &'static [&'static str] {
+        match self {
+            Network::Mainnet => &[
+                "australia.signum.network:8123",
+                "brazil.signum.network:8123",
+                "canada.signum.network:8123",
+                "europe.signum.network:8123",
+                "europe1.signum.network:8123",
+                "europe2.signum.network:8123",
+                "europe3.signum.network:8123",
+                "latam.signum.network:8123",
+                "singapore.signum.network:8123",
+                "ru.signum.network:8123",
+                "us-central.signum.network:8123",
+                "us-east.signum.network:8123",
+            ],
+            Network::Devnet => &[],
+        }
+    }
+
+    /// The port the peer to peer API listens on.
+    pub fn default_port(&self) -> u16 {
+        match self {
+            Network::Mainnet => 8123,
+            Network::Devnet => 9123,
+        }
+    }
+
+    /// The network's fork schedule, before any overrides from the configuration file.
+    pub fn historical_moments(&self) -> HistoricalMoments<Building> {
+        match self {
+            Network::Mainnet => HistoricalMoments::new(),
+            Network::Devnet => Fork::ALL
+                .into_iter()
+                .filter(|f| *f != Fork::NextFork)
+                .fold(HistoricalMoments::new(), |moments, fork| {
+                    moments.with_height(fork, 0)
+                }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Fork;
+
+    use super::Network;
+
+    #[test]
+    fn devnet_has_every_fork_active_from_genesis() {
+        let historical_moments = Network::Devnet.historical_moments().build();
+
+        assert_eq!(
+            historical_moments.active_forks(0).len(),
+            Fork::ALL.len() - 1
+        );
+        assert!(!historical_moments.is_active(Fork::NextFork, 0));
+    }
+
+    #[test]
+    fn network_deserializes_from_lowercase_name() {
+        let network: Network = serde_json::from_str(r#""devnet""#).unwrap();
+
+        assert_eq!(network, Network::Devnet);
+        assert_eq!(network.network_name(), "Signum-DEVNET");
+        assert_eq!(network.default_port(), 9123);
+    }
+
+    #[test]
+    fn mainnet_deserializes_from_its_network_name() {
+        let network: Network = serde_json::from_str(r#""Signum""#).unwrap();
+
+        assert_eq!(network, Network::Mainnet);
+    }
+}
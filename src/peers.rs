@@ -14,8 +14,8 @@ use serde_json::{json, Value};
 
 use crate::models::{
     datastore::Datastore,
-    p2p::{PeerAddress, PeerInfo},
-    Block,
+    p2p::{B1Block, B1Transaction, PeerAddress, PeerInfo},
+    Block, BlockConversionError,
 };
 
 // TODO: Move this to models or something
@@ -28,6 +28,14 @@ pub struct DownloadResult {
     pub number_of_blocks: u32,
 }
 
+/// A set of milestone block IDs returned by a peer, used to find the highest block in common.
+#[derive(Debug, Default)]
+pub struct MilestoneBlockIds {
+    pub block_ids: Vec<u64>,
+    /// Whether the peer considers the requested block to be its last block.
+    pub last: bool,
+}
+
 #[derive(Debug)]
 pub enum BlockSelect {
     BRS(B1Peer),
@@ -42,9 +50,19 @@ pub trait BasicPeerClient {
         height: u64,
         number_of_blocks: u32,
     ) -> Result<DownloadResult, PeerCommunicationError>;
+    async fn get_milestone_block_ids(
+        &self,
+        last_block_id: Option<u64>,
+        last_milestone_block_id: Option<u64>,
+    ) -> Result<MilestoneBlockIds, PeerCommunicationError>;
+    async fn get_next_block_ids(&self, block_id: u64) -> Result<Vec<u64>, PeerCommunicationError>;
+    async fn get_next_blocks(&self, block_id: u64) -> Result<Vec<B1Block>, PeerCommunicationError>;
     async fn get_peers(&self) -> Result<Vec<PeerAddress>, anyhow::Error>;
     async fn get_peer_cumulative_difficulty(&self) -> Result<BigUint>;
     async fn get_peer_info(&self) -> Result<(PeerInfo, String), PeerCommunicationError>;
+    async fn get_unconfirmed_transactions(
+        &self,
+    ) -> Result<Vec<B1Transaction>, PeerCommunicationError>;
 }
 
 /// Makes an http request to the supplied peer address and parses the returned information
@@ -143,6 +161,11 @@ pub async fn update_db_peer_info(database: Datastore, peer: PeerAddress) -> Resu
             tracing::debug!("Peer {} decoding error. Caused by:\n\t{:#?}", &peer, e);
             database.blacklist_peer(&peer).await?;
         }
+        Err(PeerCommunicationError::InvalidBlock(e)) => {
+            tracing::warn!("Peer {} sent an invalid block. Blacklisting peer.", &peer);
+            tracing::debug!("Peer {} invalid block. Caused by:\n\t{:#?}", &peer, e);
+            database.blacklist_peer(&peer).await?;
+        }
         Err(PeerCommunicationError::UnexpectedError(e)) => {
             tracing::error!(
                 "Problem getting peer info for {}. Caused by:\n\t{:#?}",
@@ -171,25 +194,6 @@ pub async fn post_peer_request(
     client.send().await
 }
 
-async fn get_peers(peer_address: &PeerAddress) -> Result<Vec<PeerAddress>, anyhow::Error> {
-    let thebody = json!({
-        "protocol": "B1",
-        "requestType": "getPeers",
-    });
-
-    let response = post_peer_request(peer_address, &thebody, None).await?;
-
-    tracing::trace!("Parsing peers...");
-    #[derive(Debug, serde::Deserialize)]
-    struct PeerContainer {
-        #[serde(rename = "peers")]
-        peers: Vec<PeerAddress>,
-    }
-    let result = response.json::<PeerContainer>().await?;
-    tracing::trace!("Peers successfully parsed: {:#?}", &result);
-    Ok(result.peers)
-}
-
 #[derive(thiserror::Error)]
 pub enum PeerCommunicationError {
     #[error("Missing announced address: {0}")]
@@ -198,6 +202,8 @@ pub enum PeerCommunicationError {
     ConnectionError(#[source] reqwest::Error),
     #[error("Connection timeout {0}")]
     ConnectionTimeout(#[source] reqwest::Error),
+    #[error("Invalid block: {0}")]
+    InvalidBlock(#[from] BlockConversionError),
     #[error(transparent)]
     UnexpectedError(#[from] anyhow::Error),
 }
@@ -221,6 +227,6 @@ pub async fn blacklist_peer(database: Datastore, peer: PeerAddress) -> Result<()
 /// De-blacklist a node. This should happen anytime this node queries it and receives
 /// a correct response, or if it talks to this node with a correct introduction.
 pub async fn deblacklist_peer(database: Datastore, peer: PeerAddress) -> Result<()> {
-    let _response = database.deblacklist_peer(peer);
+    let _response = database.deblacklist_peer(peer).await?;
     Ok(())
 }
@@ -5,13 +5,14 @@ use num_bigint::BigUint;
 use reqwest::Response;
 use serde::Deserialize;
 use serde_json::{json, Value};
+use serde_with::{serde_as, DisplayFromStr};
 
 use crate::models::{
-    p2p::{B1Block, PeerAddress, PeerInfo},
-    Block,
+    p2p::{B1Block, B1Transaction, PeerAddress, PeerInfo},
+    Block, BlockConversionError,
 };
 
-use super::{BasicPeerClient, DownloadResult, PeerCommunicationError};
+use super::{BasicPeerClient, DownloadResult, MilestoneBlockIds, PeerCommunicationError};
 
 #[derive(Debug)]
 pub struct B1Peer {
@@ -78,24 +79,181 @@ impl BasicPeerClient for B1Peer {
         struct NextBlocks {
             next_blocks: Vec<B1Block>,
         }
+
+        let values = match response.json::<NextBlocks>().await {
+            Ok(v) => Ok(v),
+            Err(e) if e.is_decode() => Err(PeerCommunicationError::ContentDecodeError(e)),
+            Err(e) => Err(PeerCommunicationError::UnexpectedError(
+                Err(e).context("could not convert body to next blocks")?,
+            )),
+        }?;
         tracing::debug!(
-            "Blocks Downloaded for {}:\n{:#?}",
-            &self.peer,
-            response.json::<NextBlocks>().await
+            "{} blocks downloaded from {}",
+            values.next_blocks.len(),
+            &self.peer
         );
 
-        let result = DownloadResult {
+        // The peer returns the blocks after `height`
+        let blocks = values
+            .next_blocks
+            .into_iter()
+            .zip(height + 1..)
+            .map(|(b1_block, block_height)| {
+                let mut block = Block::try_from(b1_block)?;
+                block.height = Some(block_height);
+                Ok(block)
+            })
+            .collect::<Result<Vec<_>, BlockConversionError>>()?;
+
+        //TODO: Verify the blocks just downloaded and return the correct Result
+        // - OK if all in this subchain are good
+        // - Verification error for bad blocks
+        Ok(DownloadResult {
             peer: self.peer.clone(),
             start_height: height,
             number_of_blocks,
-            blocks: Vec::<Block>::new(),
+            blocks,
+        })
+    }
+
+    /// Get milestone block IDs from the peer, starting either from our last block or from the
+    /// last milestone the peer gave us.
+    async fn get_milestone_block_ids(
+        &self,
+        last_block_id: Option<u64>,
+        last_milestone_block_id: Option<u64>,
+    ) -> Result<MilestoneBlockIds, PeerCommunicationError> {
+        #[serde_as]
+        #[derive(Debug, Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct MilestoneBlockIdsResponse {
+            #[serde_as(as = "Option<Vec<DisplayFromStr>>")]
+            #[serde(default)]
+            milestone_block_ids: Option<Vec<u64>>,
+            #[serde(default)]
+            last: bool,
+            error: Option<String>,
+        }
+
+        let mut thebody = json!({
+            "protocol": "B1",
+            "requestType": "getMilestoneBlockIds",
+        });
+        if let Some(last_block_id) = last_block_id {
+            thebody["lastBlockId"] = json!(last_block_id.to_string());
+        }
+        if let Some(last_milestone_block_id) = last_milestone_block_id {
+            thebody["lastMilestoneBlockId"] = json!(last_milestone_block_id.to_string());
+        }
+
+        let response = self
+            .post_peer_request(&thebody, Some(Duration::from_secs(5)))
+            .await;
+
+        let response = match response {
+            Ok(r) => Ok(r),
+            Err(e) if e.is_connect() => Err(PeerCommunicationError::ConnectionError(e)),
+            Err(e) if e.is_timeout() => Err(PeerCommunicationError::ConnectionTimeout(e)),
+            Err(e) => Err(PeerCommunicationError::UnexpectedError(
+                Err(e).context("could not get a response")?,
+            )),
+        }?;
+
+        let values = match response.json::<MilestoneBlockIdsResponse>().await {
+            Ok(v) => Ok(v),
+            Err(e) if e.is_decode() => Err(PeerCommunicationError::ContentDecodeError(e)),
+            Err(e) => Err(PeerCommunicationError::UnexpectedError(
+                Err(e).context("could not convert body to milestone block ids")?,
+            )),
+        }?;
+
+        let block_ids = match (values.milestone_block_ids, values.error) {
+            (Some(block_ids), _) => block_ids,
+            (None, Some(error)) => Err(anyhow::anyhow!(
+                "peer could not provide milestone block ids: {}",
+                error
+            ))?,
+            (None, None) => Err(anyhow::anyhow!("peer did not provide milestone block ids"))?,
         };
 
-        //TODO: Process the blocks just downloaded and return the correct Result
-        // - OK if all in this subchain are good
-        // - Connection error for any connectivity issues
-        // - Parse or Verification error for bad blocks
-        Ok(result)
+        Ok(MilestoneBlockIds {
+            block_ids,
+            last: values.last,
+        })
+    }
+
+    /// Get the IDs of the blocks following `block_id` in the peer's chain.
+    async fn get_next_block_ids(&self, block_id: u64) -> Result<Vec<u64>, PeerCommunicationError> {
+        #[serde_as]
+        #[derive(Debug, Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct NextBlockIds {
+            #[serde_as(as = "Vec<DisplayFromStr>")]
+            next_block_ids: Vec<u64>,
+        }
+
+        let thebody = json!({
+            "protocol": "B1",
+            "requestType": "getNextBlockIds",
+            "blockId": block_id.to_string(),
+        });
+
+        let response = self.post_peer_request(&thebody, None).await;
+
+        let response = match response {
+            Ok(r) => Ok(r),
+            Err(e) if e.is_connect() => Err(PeerCommunicationError::ConnectionError(e)),
+            Err(e) if e.is_timeout() => Err(PeerCommunicationError::ConnectionTimeout(e)),
+            Err(e) => Err(PeerCommunicationError::UnexpectedError(
+                Err(e).context("could not get a response")?,
+            )),
+        }?;
+
+        let values = match response.json::<NextBlockIds>().await {
+            Ok(v) => Ok(v),
+            Err(e) if e.is_decode() => Err(PeerCommunicationError::ContentDecodeError(e)),
+            Err(e) => Err(PeerCommunicationError::UnexpectedError(
+                Err(e).context("could not convert body to next block ids")?,
+            )),
+        }?;
+
+        Ok(values.next_block_ids)
+    }
+
+    /// Get the blocks following `block_id` in the peer's chain.
+    async fn get_next_blocks(&self, block_id: u64) -> Result<Vec<B1Block>, PeerCommunicationError> {
+        #[derive(Debug, Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct NextBlocks {
+            next_blocks: Vec<B1Block>,
+        }
+
+        let thebody = json!({
+            "protocol": "B1",
+            "requestType": "getNextBlocks",
+            "blockId": block_id.to_string(),
+        });
+
+        let response = self.post_peer_request(&thebody, None).await;
+
+        let response = match response {
+            Ok(r) => Ok(r),
+            Err(e) if e.is_connect() => Err(PeerCommunicationError::ConnectionError(e)),
+            Err(e) if e.is_timeout() => Err(PeerCommunicationError::ConnectionTimeout(e)),
+            Err(e) => Err(PeerCommunicationError::UnexpectedError(
+                Err(e).context("could not get a response")?,
+            )),
+        }?;
+
+        let values = match response.json::<NextBlocks>().await {
+            Ok(v) => Ok(v),
+            Err(e) if e.is_decode() => Err(PeerCommunicationError::ContentDecodeError(e)),
+            Err(e) => Err(PeerCommunicationError::UnexpectedError(
+                Err(e).context("could not convert body to next blocks")?,
+            )),
+        }?;
+
+        Ok(values.next_blocks)
     }
 
     async fn get_peers(&self) -> Result<Vec<PeerAddress>, anyhow::Error> {
@@ -162,4 +320,41 @@ impl BasicPeerClient for B1Peer {
     async fn get_peer_info(&self) -> Result<(PeerInfo, String), PeerCommunicationError> {
         todo!()
     }
+
+    /// Get the transactions in the peer's unconfirmed transaction pool.
+    async fn get_unconfirmed_transactions(
+        &self,
+    ) -> Result<Vec<B1Transaction>, PeerCommunicationError> {
+        #[derive(Debug, Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct UnconfirmedTransactions {
+            unconfirmed_transactions: Vec<B1Transaction>,
+        }
+
+        let thebody = json!({
+            "protocol": "B1",
+            "requestType": "getUnconfirmedTransactions",
+        });
+
+        let response = self.post_peer_request(&thebody, None).await;
+
+        let response = match response {
+            Ok(r) => Ok(r),
+            Err(e) if e.is_connect() => Err(PeerCommunicationError::ConnectionError(e)),
+            Err(e) if e.is_timeout() => Err(PeerCommunicationError::ConnectionTimeout(e)),
+            Err(e) => Err(PeerCommunicationError::UnexpectedError(
+                Err(e).context("could not get a response")?,
+            )),
+        }?;
+
+        let values = match response.json::<UnconfirmedTransactions>().await {
+            Ok(v) => Ok(v),
+            Err(e) if e.is_decode() => Err(PeerCommunicationError::ContentDecodeError(e)),
+            Err(e) => Err(PeerCommunicationError::UnexpectedError(
+                Err(e).context("could not convert body to unconfirmed transactions")?,
+            )),
+        }?;
+
+        Ok(values.unconfirmed_transactions)
+    }
 }
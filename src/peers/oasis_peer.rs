@@ -7,11 +7,11 @@ use serde::Deserialize;
 use serde_json::{json, Value};
 
 use crate::models::{
-    p2p::{B1Block, PeerAddress, PeerInfo},
+    p2p::{B1Block, B1Transaction, PeerAddress, PeerInfo},
     Block,
 };
 
-use super::{BasicPeerClient, DownloadResult, PeerCommunicationError};
+use super::{B1Peer, BasicPeerClient, DownloadResult, MilestoneBlockIds, PeerCommunicationError};
 
 // TODO: Refactor this to use GRPC and actually handle other oasis peers. Right now it's just a B1Peer clone
 #[derive(Debug, Default)]
@@ -92,7 +92,7 @@ impl BasicPeerClient for OasisPeer {
         tracing::debug!(
             "Blocks Downloaded for {}:\n{:#?}",
             &self.peer,
-            response.json::<NextBlocks>().await
+            response.json::<NextBlocks>().await.map(|b| b.next_blocks)
         );
 
         let result = DownloadResult {
@@ -109,6 +109,28 @@ impl BasicPeerClient for OasisPeer {
         Ok(result)
     }
 
+    async fn get_milestone_block_ids(
+        &self,
+        last_block_id: Option<u64>,
+        last_milestone_block_id: Option<u64>,
+    ) -> Result<MilestoneBlockIds, PeerCommunicationError> {
+        B1Peer::new(self.peer.clone())
+            .get_milestone_block_ids(last_block_id, last_milestone_block_id)
+            .await
+    }
+
+    async fn get_next_block_ids(&self, block_id: u64) -> Result<Vec<u64>, PeerCommunicationError> {
+        B1Peer::new(self.peer.clone())
+            .get_next_block_ids(block_id)
+            .await
+    }
+
+    async fn get_next_blocks(&self, block_id: u64) -> Result<Vec<B1Block>, PeerCommunicationError> {
+        B1Peer::new(self.peer.clone())
+            .get_next_blocks(block_id)
+            .await
+    }
+
     async fn get_peers(&self) -> Result<Vec<PeerAddress>, anyhow::Error> {
         let thebody = json!({
             "protocol": "B1",
@@ -173,9 +195,15 @@ impl BasicPeerClient for OasisPeer {
     async fn get_peer_info(&self) -> Result<(PeerInfo, String), PeerCommunicationError> {
         todo!()
     }
+
+    async fn get_unconfirmed_transactions(
+        &self,
+    ) -> Result<Vec<B1Transaction>, PeerCommunicationError> {
+        B1Peer::new(self.peer.clone())
+            .get_unconfirmed_transactions()
+            .await
+    }
 }
 
 #[derive(Debug, Default, Deserialize)]
-pub struct OasisPeerInfo {
-    some_thing: String,
-}
+pub struct OasisPeerInfo {}
@@ -0,0 +1,403 @@
+//! Proof of capacity: finding the hit a generator's plotted nonce scores against a block's
+//! generation signature, the deadline that hit gives them to forge the block, and the base
+//! target that keeps deadlines around the block time as the network's capacity changes.
+
+mod shabal;
+
+use num_bigint::BigUint;
+pub use shabal::Shabal256;
+
+use crate::{
+    flux_capacitor::BLOCK_TIME,
+    models::{AccountId, Block},
+    Active, HistoricalMoments,
+};
+
+const HASH_SIZE: usize = 32;
+/// The most bytes of a nonce's plot each of its hashes covers.
+const HASH_CAP: usize = 4096;
+const SCOOP_SIZE: usize = 64;
+pub const SCOOPS_PER_NONCE: usize = 4096;
+const NONCE_SIZE: usize = SCOOP_SIZE * SCOOPS_PER_NONCE;
+
+/// The base target of the first blocks, which is also the highest it may be adjusted to.
+pub const INITIAL_BASE_TARGET: u64 = 18_325_193_796;
+pub const MAX_BASE_TARGET: u64 = INITIAL_BASE_TARGET;
+/// The height from which the base target is adjusted over the last 24 blocks instead of the
+/// last 4.
+const BASE_TARGET_ADJUSTMENT_CHANGE: u64 = 2_700;
+
+/// The layout of plot files.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PocVersion {
+    /// The original layout, used until the `poc2_enable` height.
+    Poc1,
+    /// Swaps the second hash of each scoop with that of its mirror scoop, so a miner needs
+    /// the whole nonce instead of only half of it to read a scoop.
+    Poc2,
+}
+
+impl PocVersion {
+    /// Returns the layout blocks at `height` are mined with.
+    pub fn at_height(height: u64, historical_moments: &HistoricalMoments<Active>) -> Self {
+        if height >= u64::from(historical_moments.poc2_enable()) {
+            Self::Poc2
+        } else {
+            Self::Poc1
+        }
+    }
+}
+
+/// Returns which scoop of every nonce is used to mine the block at `height`.
+pub fn calculate_scoop(generation_signature: &[u8; 32], height: u64) -> usize {
+    let hash = Shabal256::new()
+        .chain_update(generation_signature)
+        .chain_update(height.to_be_bytes())
+        .finalize();
+    usize::from(u16::from_be_bytes([hash[30], hash[31]])) % SCOOPS_PER_NONCE
+}
+
+/// Returns the hit `scoop` of the account's nonce scores against the generation signature.
+pub fn calculate_hit(
+    account_id: AccountId,
+    nonce: u64,
+    generation_signature: &[u8; 32],
+    scoop: usize,
+    poc_version: PocVersion,
+) -> u64 {
+    let scoop_data = plot_scoop(account_id, nonce, scoop, poc_version);
+    let hash = Shabal256::new()
+        .chain_update(generation_signature)
+        .chain_update(scoop_data)
+        .finalize();
+    u64::from_le_bytes(hash[..8].try_into().expect("slice is 8 bytes"))
+}
+
+/// Returns how many seconds after the previous block a hit allows its generator to forge.
+///
+/// From the `sodium_enable` height, deadlines are scaled logarithmically so they spread
+/// around the target block time instead of favouring very short blocks.
+pub fn calculate_deadline(
+    hit: u64,
+    base_target: u64,
+    height: u64,
+    historical_moments: &HistoricalMoments<Active>,
+) -> u64 {
+    let deadline = hit / base_target.max(1);
+    if height >= u64::from(historical_moments.sodium_enable()) && deadline > 0 {
+        // Scaled so a deadline of one block time stays (about) one block time
+        let block_time = BLOCK_TIME.get_flux_value(height, historical_moments) as f64;
+        let ln_scale = block_time / block_time.ln();
+        ((deadline as f64).ln() * ln_scale) as u64
+    } else {
+        deadline
+    }
+}
+
+/// Returns the generation signature of the block following `previous_block`, which chains the
+/// previous generation signature with the previous generator.
+pub fn calculate_generation_signature(previous_block: &Block) -> [u8; 32] {
+    let previous_generator = AccountId::from_public_key(&previous_block.generator_public_key);
+    Shabal256::new()
+        .chain_update(previous_block.generation_signature)
+        .chain_update(u64::from(previous_generator).to_be_bytes())
+        .finalize()
+}
+
+/// Returns the base target of the block at `height` forged at `timestamp`.
+///
+/// `recent_blocks` are the blocks before it, oldest first, ending with the block at
+/// `height - 1`. Returns `None` if they don't reach back far enough to average over.
+pub fn calculate_base_target(
+    height: u64,
+    timestamp: u64,
+    recent_blocks: &[Block],
+    historical_moments: &HistoricalMoments<Active>,
+) -> Option<u64> {
+    let block_time = BLOCK_TIME.get_flux_value(height, historical_moments);
+
+    if height < 4 {
+        return Some(INITIAL_BASE_TARGET);
+    }
+
+    if height < BASE_TARGET_ADJUSTMENT_CHANGE {
+        let window = recent_blocks.get(recent_blocks.len().checked_sub(4)?..)?;
+        let average = window.iter().map(|b| b.base_target).sum::<u64>() / 4;
+        let elapsed = timestamp.saturating_sub(window[0].timestamp);
+
+        let base_target = (u128::from(average) * u128::from(elapsed) / u128::from(block_time * 4))
+            .min(u128::from(MAX_BASE_TARGET)) as u64;
+        let base_target = base_target.max(average * 9 / 10).max(1);
+        return Some(base_target.min(average * 11 / 10));
+    }
+
+    // A running average that weighs the older blocks more heavily, as BRS computes it
+    let window = recent_blocks.get(recent_blocks.len().checked_sub(24)?..)?;
+    let previous_base_target = window[23].base_target;
+    let average = window[..23].iter().rev().zip(2u128..).fold(
+        u128::from(previous_base_target),
+        |average, (block, count)| (average * count + u128::from(block.base_target)) / (count + 1),
+    );
+    let target_timespan = block_time * 24;
+    let elapsed = timestamp
+        .saturating_sub(window[0].timestamp)
+        .clamp(target_timespan / 2, target_timespan * 2);
+
+    let base_target = (average * u128::from(elapsed) / u128::from(target_timespan))
+        .min(u128::from(MAX_BASE_TARGET)) as u64;
+    Some(base_target.max(1).clamp(
+        previous_base_target * 8 / 10,
+        previous_base_target * 12 / 10,
+    ))
+}
+
+/// Returns the cumulative difficulty of a block with `base_target` on top of a chain with
+/// `previous_cumulative_difficulty`.
+///
+/// Each block adds `2^64 / base_target`, the number of hits expected to have been tried to
+/// forge it, so the chain with the most work behind it has the highest cumulative difficulty.
+pub fn calculate_cumulative_difficulty(
+    previous_cumulative_difficulty: &BigUint,
+    base_target: u64,
+) -> BigUint {
+    previous_cumulative_difficulty + (BigUint::from(1u8) << 64) / base_target.max(1)
+}
+
+/// Returns the deadline the block's generator had with its nonce.
+///
+/// The hit is scaled by the base target of `previous_block`, which was the difficulty in effect
+/// while the block was being forged. The block's height must be set, as the scoop and plot
+/// layout depend on it.
+pub fn block_deadline(
+    block: &Block,
+    previous_block: &Block,
+    historical_moments: &HistoricalMoments<Active>,
+) -> Option<u64> {
+    let height = block.height?;
+    let scoop = calculate_scoop(&block.generation_signature, height);
+    let hit = calculate_hit(
+        AccountId::from_public_key(&block.generator_public_key),
+        block.nonce,
+        &block.generation_signature,
+        scoop,
+        PocVersion::at_height(height, historical_moments),
+    );
+    Some(calculate_deadline(
+        hit,
+        previous_block.base_target,
+        height,
+        historical_moments,
+    ))
+}
+
+/// Plots the account's nonce and returns one scoop of it.
+fn plot_scoop(account_id: AccountId, nonce: u64, scoop: usize, poc_version: PocVersion) -> Vec<u8> {
+    // Each hash covers the up to 4096 bytes after it, starting from the account and nonce
+    // seed at the end of the plot.
+    let mut plot = vec![0u8; NONCE_SIZE + 16];
+    plot[NONCE_SIZE..NONCE_SIZE + 8].copy_from_slice(&u64::from(account_id).to_be_bytes());
+    plot[NONCE_SIZE + 8..].copy_from_slice(&nonce.to_be_bytes());
+    for end in (HASH_SIZE..=NONCE_SIZE).rev().step_by(HASH_SIZE) {
+        let length = (plot.len() - end).min(HASH_CAP);
+        let hash = Shabal256::digest(&plot[end..end + length]);
+        plot[end - HASH_SIZE..end].copy_from_slice(&hash);
+    }
+    let final_hash = Shabal256::digest(&plot);
+
+    let second_hash_scoop = match poc_version {
+        PocVersion::Poc1 => scoop,
+        PocVersion::Poc2 => SCOOPS_PER_NONCE - 1 - scoop,
+    };
+    let first_hash = scoop * SCOOP_SIZE;
+    let second_hash = second_hash_scoop * SCOOP_SIZE + HASH_SIZE;
+    plot[first_hash..first_hash + HASH_SIZE]
+        .iter()
+        .chain(&plot[second_hash..second_hash + HASH_SIZE])
+        .enumerate()
+        .map(|(i, byte)| byte ^ final_hash[i % HASH_SIZE])
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        models::{p2p::B1Block, AccountId, Block},
+        HistoricalMoments,
+    };
+
+    use num_bigint::BigUint;
+
+    use super::{
+        block_deadline, calculate_base_target, calculate_cumulative_difficulty, calculate_deadline,
+        calculate_hit, calculate_scoop, PocVersion, INITIAL_BASE_TARGET,
+    };
+
+    /// The height of the mainnet block in the fixture.
+    const FIXTURE_HEIGHT: u64 = 1_236_943;
+
+    fn fixture_block() -> Block {
+        let mut block = Block::try_from(
+            serde_json::from_str::<B1Block>(include_str!("../tests/fixtures/b1_block.json"))
+                .unwrap(),
+        )
+        .unwrap();
+        block.height = Some(FIXTURE_HEIGHT);
+        block
+    }
+
+    /// Returns `count` blocks with the given base target, forged `spacing` seconds apart.
+    fn recent_blocks(count: u64, base_target: u64, spacing: u64) -> Vec<Block> {
+        (0..count)
+            .map(|i| {
+                let mut block = fixture_block();
+                block.base_target = base_target;
+                block.timestamp = 1_000_000 + i * spacing;
+                block
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fixture_block_nonce_scores_its_mainnet_hit() {
+        let block = fixture_block();
+
+        let scoop = calculate_scoop(&block.generation_signature, FIXTURE_HEIGHT);
+        assert_eq!(scoop, 1947);
+
+        let hit = calculate_hit(
+            AccountId::from_public_key(&block.generator_public_key),
+            block.nonce,
+            &block.generation_signature,
+            scoop,
+            PocVersion::Poc2,
+        );
+        assert_eq!(hit, 1_654_360_177);
+    }
+
+    #[test]
+    fn block_deadline_uses_previous_base_target_and_requires_height() {
+        let mut block = fixture_block();
+        block.base_target = 1;
+        let mut previous_block = fixture_block();
+        previous_block.base_target = 1_000_000;
+        let historical_moments = HistoricalMoments::new().build();
+
+        assert_eq!(
+            block_deadline(&block, &previous_block, &historical_moments),
+            Some(calculate_deadline(
+                1_654_360_177,
+                1_000_000,
+                FIXTURE_HEIGHT,
+                &historical_moments
+            ))
+        );
+
+        block.height = None;
+        assert_eq!(
+            block_deadline(&block, &previous_block, &historical_moments),
+            None
+        );
+    }
+
+    #[test]
+    fn deadlines_are_scaled_logarithmically_from_sodium() {
+        let historical_moments = HistoricalMoments::new().build();
+        let sodium_enable = u64::from(historical_moments.sodium_enable());
+
+        assert_eq!(
+            calculate_deadline(1_000 * 100, 1_000, sodium_enable - 1, &historical_moments),
+            100
+        );
+        assert_eq!(
+            calculate_deadline(1_000 * 100, 1_000, sodium_enable, &historical_moments),
+            201
+        );
+        assert_eq!(
+            calculate_deadline(1_000 * 3_600, 1_000, sodium_enable - 1, &historical_moments),
+            3_600
+        );
+        assert_eq!(
+            calculate_deadline(1_000 * 3_600, 1_000, sodium_enable, &historical_moments),
+            358
+        );
+        assert_eq!(
+            calculate_deadline(999, 1_000, sodium_enable, &historical_moments),
+            0
+        );
+    }
+
+    #[test]
+    fn base_target_follows_block_times_over_last_4_blocks_before_2700() {
+        let historical_moments = HistoricalMoments::new().build();
+
+        assert_eq!(
+            calculate_base_target(3, 0, &[], &historical_moments),
+            Some(INITIAL_BASE_TARGET)
+        );
+
+        let blocks = recent_blocks(4, 1_000_000, 240);
+        let next_timestamp = blocks[3].timestamp + 240;
+        assert_eq!(
+            calculate_base_target(100, next_timestamp, &blocks, &historical_moments),
+            Some(1_000_000)
+        );
+        // Blocks coming too fast raise the difficulty by at most 10%
+        assert_eq!(
+            calculate_base_target(100, next_timestamp - 480, &blocks, &historical_moments),
+            Some(900_000)
+        );
+        assert_eq!(
+            calculate_base_target(100, next_timestamp, &blocks[1..], &historical_moments),
+            None
+        );
+    }
+
+    #[test]
+    fn base_target_follows_block_times_over_last_24_blocks_from_2700() {
+        let historical_moments = HistoricalMoments::new().build();
+
+        let blocks = recent_blocks(24, 1_000_000, 240);
+        let next_timestamp = blocks[23].timestamp + 240;
+        assert_eq!(
+            calculate_base_target(3_000, next_timestamp, &blocks, &historical_moments),
+            Some(1_000_000)
+        );
+        // Blocks coming too slowly lower the difficulty by at most 20%
+        let blocks = recent_blocks(24, 1_000_000, 480);
+        let next_timestamp = blocks[23].timestamp + 480;
+        assert_eq!(
+            calculate_base_target(3_000, next_timestamp, &blocks, &historical_moments),
+            Some(1_200_000)
+        );
+        assert_eq!(
+            calculate_base_target(3_000, next_timestamp, &blocks[1..], &historical_moments),
+            None
+        );
+    }
+
+    #[test]
+    fn cumulative_difficulty_adds_expected_hits_of_each_block() {
+        let genesis = BigUint::ZERO;
+
+        let block_1 = calculate_cumulative_difficulty(&genesis, INITIAL_BASE_TARGET);
+        assert_eq!(block_1, BigUint::from(1_006_632_960u64));
+
+        let block_2 = calculate_cumulative_difficulty(&block_1, INITIAL_BASE_TARGET / 2);
+        assert_eq!(block_2, BigUint::from(1_006_632_960u64 + 2_013_265_920));
+    }
+
+    #[test]
+    fn poc2_layout_starts_at_poc2_enable() {
+        let historical_moments = HistoricalMoments::new().build();
+        let poc2_enable = u64::from(historical_moments.poc2_enable());
+
+        assert_eq!(
+            PocVersion::at_height(poc2_enable - 1, &historical_moments),
+            PocVersion::Poc1
+        );
+        assert_eq!(
+            PocVersion::at_height(poc2_enable, &historical_moments),
+            PocVersion::Poc2
+        );
+    }
+}
@@ -0,0 +1,190 @@
+//! Shabal-256, the hash proof of capacity plots and scoops are built with.
+
+/// The state after processing the prefix blocks that select a 256 bit output.
+const A_INIT: [u32; 12] = [
+    0x52F84552, 0xE54B7999, 0x2D8EE3EC, 0xB9645191, 0xE0078B86, 0xBB7C44C9, 0xD2B5C1CA, 0xB0D2EB8C,
+    0x14CE5A45, 0x22AF50DC, 0xEFFDBC6B, 0xEB21B74A,
+];
+const B_INIT: [u32; 16] = [
+    0xB555C6EE, 0x3E710596, 0xA72A652F, 0x9301515F, 0xDA28C1FA, 0x696FD868, 0x9CB6BF72, 0x0AFE4002,
+    0xA6E03615, 0x5138C1D4, 0xBE216306, 0xB38B8890, 0x3EA8B96B, 0x3299ACE4, 0x30924DD4, 0x55CB34A5,
+];
+const C_INIT: [u32; 16] = [
+    0xB405F031, 0xC4233EBA, 0xB3733979, 0xC0DD9D55, 0xC51C28AE, 0xA327B8E1, 0x56C56167, 0xED614433,
+    0x88B59D60, 0x60E2CEBA, 0x758B4B8B, 0x83E82A7F, 0xBC968828, 0xE6E00BF7, 0xBA839E55, 0x9B491C60,
+];
+
+const BLOCK_SIZE: usize = 64;
+
+/// An incremental Shabal-256 hasher.
+#[derive(Clone)]
+pub struct Shabal256 {
+    a: [u32; 12],
+    b: [u32; 16],
+    c: [u32; 16],
+    /// The number of the block being processed, starting at 1.
+    w: u64,
+    buffer: [u8; BLOCK_SIZE],
+    buffer_len: usize,
+}
+
+impl Default for Shabal256 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shabal256 {
+    pub fn new() -> Self {
+        Self {
+            a: A_INIT,
+            b: B_INIT,
+            c: C_INIT,
+            w: 1,
+            buffer: [0; BLOCK_SIZE],
+            buffer_len: 0,
+        }
+    }
+
+    /// Returns the hash of `data`.
+    pub fn digest(data: impl AsRef<[u8]>) -> [u8; 32] {
+        Self::new().chain_update(data).finalize()
+    }
+
+    pub fn update(&mut self, data: impl AsRef<[u8]>) {
+        let mut data = data.as_ref();
+
+        if self.buffer_len > 0 {
+            let take = data.len().min(BLOCK_SIZE - self.buffer_len);
+            self.buffer[self.buffer_len..self.buffer_len + take].copy_from_slice(&data[..take]);
+            self.buffer_len += take;
+            data = &data[take..];
+            if self.buffer_len < BLOCK_SIZE {
+                return;
+            }
+            let block = decode_block(&self.buffer);
+            self.compress(&block);
+            self.buffer_len = 0;
+        }
+
+        let mut blocks = data.chunks_exact(BLOCK_SIZE);
+        for block in &mut blocks {
+            self.compress(&decode_block(block));
+        }
+        let rest = blocks.remainder();
+        self.buffer[..rest.len()].copy_from_slice(rest);
+        self.buffer_len = rest.len();
+    }
+
+    pub fn chain_update(mut self, data: impl AsRef<[u8]>) -> Self {
+        self.update(data);
+        self
+    }
+
+    pub fn finalize(mut self) -> [u8; 32] {
+        // Pad with a single set bit, then finish with the padded block and three extra rounds
+        // over it, without the counter moving on.
+        self.buffer[self.buffer_len] = 0x80;
+        self.buffer[self.buffer_len + 1..].fill(0);
+        let m = decode_block(&self.buffer);
+
+        self.add_block(&m);
+        self.xor_counter();
+        self.permute(&m);
+        for _ in 0..3 {
+            std::mem::swap(&mut self.b, &mut self.c);
+            self.xor_counter();
+            self.permute(&m);
+        }
+
+        let mut hash = [0; 32];
+        for (bytes, word) in hash.chunks_exact_mut(4).zip(&self.b[8..]) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+        hash
+    }
+
+    fn compress(&mut self, m: &[u32; 16]) {
+        self.add_block(m);
+        self.xor_counter();
+        self.permute(m);
+        for (c, m) in self.c.iter_mut().zip(m) {
+            *c = c.wrapping_sub(*m);
+        }
+        std::mem::swap(&mut self.b, &mut self.c);
+        self.w = self.w.wrapping_add(1);
+    }
+
+    fn add_block(&mut self, m: &[u32; 16]) {
+        for (b, m) in self.b.iter_mut().zip(m) {
+            *b = b.wrapping_add(*m);
+        }
+    }
+
+    fn xor_counter(&mut self) {
+        self.a[0] ^= self.w as u32;
+        self.a[1] ^= (self.w >> 32) as u32;
+    }
+
+    /// The keyed permutation at the core of Shabal.
+    fn permute(&mut self, m: &[u32; 16]) {
+        let (a, b, c) = (&mut self.a, &mut self.b, &self.c);
+
+        for b in b.iter_mut() {
+            *b = b.rotate_left(17);
+        }
+        for step in 0..48 {
+            let i = step % 16;
+            let a0 = step % 12;
+            let a1 = (step + 11) % 12;
+            a[a0] = (a[a0] ^ a[a1].rotate_left(15).wrapping_mul(5) ^ c[(24 - i) % 16])
+                .wrapping_mul(3)
+                ^ b[(i + 13) % 16]
+                ^ (b[(i + 9) % 16] & !b[(i + 6) % 16])
+                ^ m[i];
+            b[i] = !(b[i].rotate_left(1) ^ a[a0]);
+        }
+        for j in 0..36 {
+            a[j % 12] = a[j % 12].wrapping_add(c[(j + 3) % 16]);
+        }
+    }
+}
+
+fn decode_block(bytes: &[u8]) -> [u32; 16] {
+    let mut words = [0; 16];
+    for (word, bytes) in words.iter_mut().zip(bytes.chunks_exact(4)) {
+        *word = u32::from_le_bytes(bytes.try_into().expect("chunk is 4 bytes"));
+    }
+    words
+}
+
+#[cfg(test)]
+mod test {
+    use super::Shabal256;
+
+    #[test]
+    fn shabal256_matches_reference_test_vectors() {
+        assert_eq!(
+            hex::encode(Shabal256::digest(b"")),
+            "aec750d11feee9f16271922fbaf5a9be142f62019ef8d720f858940070889014"
+        );
+        assert_eq!(
+            hex::encode(Shabal256::digest(
+                b"abcdefghijklmnopqrstuvwxyz-0123456789-ABCDEFGHIJKLMNOPQRSTUVWXYZ-0123456789-abcdefghijklmnopqrstuvwxyz"
+            )),
+            "b49f34bf51864c30533cc46cc2542bdec2f96fd06f5c539aff6ead5883f7327a"
+        );
+    }
+
+    #[test]
+    fn incremental_updates_match_one_shot_digest() {
+        let data = (0..=255u8).cycle().take(1000).collect::<Vec<_>>();
+
+        let mut hasher = Shabal256::new();
+        for chunk in data.chunks(37) {
+            hasher.update(chunk);
+        }
+
+        assert_eq!(hasher.finalize(), Shabal256::digest(&data));
+    }
+}
@@ -3,8 +3,16 @@ pub mod request_models;
 
 mod add_peers;
 mod application;
+mod get_blocks_from_height;
+mod get_cumulative_difficulty;
 mod get_info;
+mod get_milestone_block_ids;
+mod get_next_block_ids;
+mod get_next_blocks;
 mod get_peers;
+mod get_unconfirmed_transactions;
+mod process_block;
+mod process_transactions;
 mod signum_api_handler;
 
 pub use application::*;
@@ -1,5 +1,60 @@
+use std::str::FromStr;
+
 use actix_web::HttpResponse;
+use tracing::Instrument;
+
+use crate::{
+    models::{datastore::Datastore, p2p::PeerAddress},
+    peers::update_db_peer_info,
+    srs_api::SignumApiError,
+};
+
+/// The maximum number of peer addresses handled from a single request.
+const MAX_PEERS_PER_REQUEST: usize = 100;
+
+#[tracing::instrument(skip(database))]
+pub(crate) async fn add_peers_handler(
+    database: &Datastore,
+    peers: Vec<String>,
+) -> Result<HttpResponse, SignumApiError> {
+    let mut database = database.clone();
+
+    if peers.len() > MAX_PEERS_PER_REQUEST {
+        tracing::debug!(
+            "Only handling the first {} of {} peers from addPeers.",
+            MAX_PEERS_PER_REQUEST,
+            peers.len()
+        );
+    }
+
+    let mut new_peers_count = 0;
+    for peer in peers.into_iter().take(MAX_PEERS_PER_REQUEST) {
+        let peer_address = match PeerAddress::from_str(&peer) {
+            Ok(peer_address) => peer_address,
+            Err(e) => {
+                tracing::debug!("Ignoring invalid peer address '{}': {}", &peer, e);
+                continue;
+            }
+        };
+
+        match database.create_new_peer(&peer_address).await {
+            Ok(mut r) => {
+                if r.take::<Vec<String>>("announced_address").is_ok() {
+                    tracing::debug!("Saved new peer {}", &peer_address);
+                    tokio::spawn(
+                        update_db_peer_info(database.clone(), peer_address).in_current_span(),
+                    );
+                    new_peers_count += 1;
+                } else {
+                    tracing::trace!("Already have peer {}", &peer_address);
+                }
+            }
+            Err(e) => {
+                tracing::error!("Unable to save peer: {:?}", e);
+            }
+        }
+    }
 
-pub(crate) fn add_peers_handler(_peers: Vec<String>) -> Result<HttpResponse, actix_web::Error> {
+    tracing::debug!("Added {} new peers from addPeers.", new_peers_count);
     Ok(HttpResponse::Ok().finish())
 }
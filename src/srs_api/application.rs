@@ -1,5 +1,7 @@
 use std::net::TcpListener;
 
+use std::sync::Arc;
+
 use actix_web::{
     dev::Server,
     web::{self, Data},
@@ -11,7 +13,8 @@ use crate::{
     configuration::{PeerToPeerSettings, Settings},
     health_check,
     models::datastore::Datastore,
-    srs_api::signum_api_handler,
+    srs_api::{signum_api_handler, SignumApiError},
+    Active, HistoricalMoments,
 };
 
 pub struct SrsApiApplication {
@@ -23,6 +26,7 @@ impl SrsApiApplication {
     pub async fn build(
         configuration: Settings,
         database: Datastore,
+        historical_moments: Arc<HistoricalMoments<Active>>,
     ) -> Result<Self, anyhow::Error> {
         let address = format!(
             "{}:{}",
@@ -37,6 +41,7 @@ impl SrsApiApplication {
             database,
             configuration.srs_api.base_url,
             configuration.p2p.clone(),
+            historical_moments,
         )
         .await?;
 
@@ -65,22 +70,29 @@ async fn run(
     db: Datastore,
     base_url: String,
     p2p_settings: PeerToPeerSettings,
+    historical_moments: Arc<HistoricalMoments<Active>>,
 ) -> Result<Server, anyhow::Error> {
     let db = Data::new(db);
     let base_url = Data::new(ApplicationBaseUrl(base_url));
     let p2p_settings = Data::new(p2p_settings);
+    let historical_moments = Data::from(historical_moments);
 
-    let server = HttpServer::new(move || {
-        App::new()
-            .wrap(TracingLogger::default())
-            .route("/health_check", web::get().to(health_check))
-            .route("/{allroutes:.*}", web::post().to(signum_api_handler))
-            .app_data(db.clone())
-            .app_data(base_url.clone())
-            .app_data(p2p_settings.clone())
-    })
-    .listen(listener)?
-    .run();
+    let server =
+        HttpServer::new(move || {
+            App::new()
+                .wrap(TracingLogger::default())
+                .route("/health_check", web::get().to(health_check))
+                .route("/{allroutes:.*}", web::post().to(signum_api_handler))
+                .app_data(db.clone())
+                .app_data(base_url.clone())
+                .app_data(p2p_settings.clone())
+                .app_data(historical_moments.clone())
+                .app_data(web::JsonConfig::default().error_handler(|e, _request| {
+                    SignumApiError::InvalidPayload(e.to_string()).into()
+                }))
+        })
+        .listen(listener)?
+        .run();
 
     Ok(server)
 }
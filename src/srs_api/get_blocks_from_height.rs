@@ -0,0 +1,40 @@
+use actix_web::HttpResponse;
+use serde::Serialize;
+
+use crate::{
+    models::{datastore::Datastore, p2p::B1Block},
+    srs_api::SignumApiError,
+};
+
+/// The number of blocks returned when the peer doesn't ask for a valid number.
+const DEFAULT_NUM_BLOCKS: u32 = 100;
+/// The maximum number of blocks returned in a single response.
+const MAX_NUM_BLOCKS: u32 = 1400;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NextBlocksResponse {
+    next_blocks: Vec<B1Block>,
+}
+
+/// Returns up to `num_blocks` blocks following the block at `height` in our chain.
+///
+/// Like BRS, the block at `height` itself is not included, and requests for fewer than one
+/// or more than [`MAX_NUM_BLOCKS`] blocks fall back to [`DEFAULT_NUM_BLOCKS`].
+#[tracing::instrument(skip(database))]
+pub(crate) async fn get_blocks_from_height_handler(
+    height: u64,
+    num_blocks: Option<u32>,
+    database: &Datastore,
+) -> Result<HttpResponse, SignumApiError> {
+    let num_blocks = num_blocks
+        .filter(|n| (1..=MAX_NUM_BLOCKS).contains(n))
+        .unwrap_or(DEFAULT_NUM_BLOCKS);
+
+    let next_blocks = match database.get_block_id_at_height(height).await? {
+        Some(block_id) => database.get_blocks_after(block_id, num_blocks).await?,
+        None => Vec::new(),
+    };
+
+    Ok(HttpResponse::Ok().json(NextBlocksResponse { next_blocks }))
+}
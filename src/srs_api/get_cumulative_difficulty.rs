@@ -0,0 +1,26 @@
+use actix_web::HttpResponse;
+use serde::Serialize;
+
+use crate::{models::datastore::Datastore, srs_api::SignumApiError};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CumulativeDifficultyResponse {
+    cumulative_difficulty: String,
+    blockchain_height: u64,
+}
+
+pub(crate) async fn get_cumulative_difficulty_handler(
+    database: &Datastore,
+) -> Result<HttpResponse, SignumApiError> {
+    let (blockchain_height, cumulative_difficulty) = database
+        .get_chain_tip()
+        .await?
+        .map(|(_, height, cumulative_difficulty)| (height, cumulative_difficulty))
+        .unwrap_or_default();
+
+    Ok(HttpResponse::Ok().json(CumulativeDifficultyResponse {
+        cumulative_difficulty: cumulative_difficulty.to_string(),
+        blockchain_height,
+    }))
+}
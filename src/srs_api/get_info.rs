@@ -1,17 +1,114 @@
+use std::{net::IpAddr, str::FromStr};
+
 use actix_web::HttpResponse;
+use serde_json::json;
 
-use crate::configuration::PeerToPeerSettings;
+use crate::{
+    configuration::PeerToPeerSettings,
+    models::{
+        datastore::Datastore,
+        p2p::{PeerAddress, PeerInfo},
+    },
+    peers::deblacklist_peer,
+};
 
 use super::{
     outgoing_json::{OutgoingJsonBuiler, OutgoingRequest},
     request_models::GetInfoRequestModel,
+    SignumApiError,
 };
 
-pub(crate) fn get_info_handler(
-    _model: GetInfoRequestModel,
+#[tracing::instrument(skip(database, settings))]
+pub(crate) async fn get_info_handler(
+    model: GetInfoRequestModel,
+    remote_ip: Option<String>,
+    database: &Datastore,
     settings: &PeerToPeerSettings,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, SignumApiError> {
+    if model.network_name != settings.network_name {
+        tracing::debug!(
+            "Rejecting peer on network '{}', expected '{}'",
+            &model.network_name,
+            &settings.network_name
+        );
+        return Ok(HttpResponse::BadRequest().json(json!({
+            "error": format!("Wrong network: {}", &model.network_name),
+        })));
+    }
+
+    // Prefer the announced address, but fall back to the address the request came from
+    let peer_address = model
+        .announced_address
+        .as_deref()
+        .filter(|a| !a.is_empty())
+        .and_then(|a| PeerAddress::from_str(a).ok())
+        .or_else(|| {
+            remote_ip
+                .as_deref()
+                .and_then(|ip| PeerAddress::from_str(ip).ok())
+        });
+
+    match (peer_address, remote_ip) {
+        (Some(peer_address), Some(remote_ip)) => {
+            // Only the peer behind an address may update or deblacklist it
+            if resolves_to(&peer_address, &remote_ip).await {
+                record_peer(database, peer_address, remote_ip, model).await?;
+            } else {
+                tracing::debug!(
+                    "Not recording peer {}: it doesn't resolve to the calling IP {}",
+                    &peer_address,
+                    &remote_ip
+                );
+            }
+        }
+        _ => tracing::debug!("Unable to determine an address for the calling peer"),
+    }
+
     let myinfo = OutgoingJsonBuiler::new(settings).get_info().finish()?;
     Ok(HttpResponse::Ok().json(myinfo))
-    // HttpResponse::Ok().finish()
+}
+
+/// Returns whether the peer address resolves to the IP address a request came from.
+async fn resolves_to(peer_address: &PeerAddress, remote_ip: &str) -> bool {
+    let Ok(remote_ip) = IpAddr::from_str(remote_ip) else {
+        return false;
+    };
+
+    match tokio::net::lookup_host(peer_address.to_string()).await {
+        Ok(mut addresses) => addresses.any(|a| a.ip() == remote_ip),
+        Err(e) => {
+            tracing::debug!("Unable to resolve {}: {}", peer_address, e);
+            false
+        }
+    }
+}
+
+/// Stores the information a peer introduced itself with and lifts any blacklisting, since
+/// a correct introduction means the peer is behaving.
+async fn record_peer(
+    database: &Datastore,
+    peer_address: PeerAddress,
+    remote_ip: String,
+    model: GetInfoRequestModel,
+) -> Result<(), SignumApiError> {
+    let mut database = database.clone();
+
+    // Creating a peer that already exists is rejected by the unique index, so this is safe
+    database.create_new_peer(&peer_address).await?;
+
+    let peer_info = PeerInfo {
+        announced_address: Some(peer_address.clone()),
+        application: model.application.unwrap_or_default(),
+        version: model.version.unwrap_or_default(),
+        platform: model.platform,
+        share_address: model.share_address.unwrap_or_default(),
+        network_name: model.network_name,
+    };
+    database
+        .update_peer_info(peer_address.clone(), remote_ip, peer_info)
+        .await?;
+
+    deblacklist_peer(database, peer_address).await?;
+
+    Ok(())
 }
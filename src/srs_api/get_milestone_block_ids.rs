@@ -0,0 +1,85 @@
+use actix_web::HttpResponse;
+use serde::Serialize;
+use serde_json::json;
+use serde_with::{serde_as, DisplayFromStr};
+
+use crate::models::datastore::Datastore;
+
+use super::{request_models::GetMilestoneBlockIdsRequestModel, SignumApiError};
+
+/// The maximum number of milestone block IDs returned in a single response.
+const MILESTONE_LIMIT: usize = 10;
+/// The distance between milestones when starting from the peer's last block.
+const MILESTONE_JUMP: u64 = 10;
+/// The maximum distance between milestones when continuing from a previous milestone.
+const MAX_MILESTONE_JUMP: u64 = 1440;
+
+#[serde_as]
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MilestoneBlockIdsResponse {
+    #[serde_as(as = "Vec<DisplayFromStr>")]
+    milestone_block_ids: Vec<u64>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    last: bool,
+}
+
+/// Returns a list of block IDs from our chain that a peer can use to find the highest block
+/// we have in common.
+///
+/// If the peer's `last_block_id` is in our chain it is returned on its own, flagged as `last`
+/// if it is also our tip. Otherwise milestones are returned walking down from our tip every
+/// 10 blocks, or, when continuing from a `last_milestone_block_id`, with a jump equal to the
+/// distance from that milestone to our tip (capped at 1440 blocks).
+#[tracing::instrument(skip(database))]
+pub(crate) async fn get_milestone_block_ids_handler(
+    model: GetMilestoneBlockIdsRequestModel,
+    database: &Datastore,
+) -> Result<HttpResponse, SignumApiError> {
+    let Some((my_last_block_id, blockchain_height, _)) = database.get_chain_tip().await? else {
+        return Ok(HttpResponse::Ok().json(MilestoneBlockIdsResponse::default()));
+    };
+
+    if let Some(last_block_id) = model.last_block_id {
+        if last_block_id == my_last_block_id
+            || database.get_block_height(last_block_id).await?.is_some()
+        {
+            return Ok(HttpResponse::Ok().json(MilestoneBlockIdsResponse {
+                milestone_block_ids: vec![last_block_id],
+                last: last_block_id == my_last_block_id,
+            }));
+        }
+    }
+
+    let (mut height, jump) = if let Some(last_milestone_block_id) = model.last_milestone_block_id {
+        let Some(milestone_height) = database.get_block_height(last_milestone_block_id).await?
+        else {
+            return Ok(HttpResponse::BadRequest().json(json!({
+                "error": format!("Don't have block {}", last_milestone_block_id),
+            })));
+        };
+        let jump = blockchain_height
+            .saturating_sub(milestone_height)
+            .clamp(1, MAX_MILESTONE_JUMP);
+        (milestone_height.saturating_sub(jump), jump)
+    } else if model.last_block_id.is_some() {
+        (blockchain_height, MILESTONE_JUMP)
+    } else {
+        return Ok(HttpResponse::BadRequest().json(json!({
+            "error": "Old getMilestoneBlockIds protocol not supported, please upgrade",
+        })));
+    };
+
+    let mut milestone_block_ids = Vec::with_capacity(MILESTONE_LIMIT);
+    while height > 0 && milestone_block_ids.len() < MILESTONE_LIMIT {
+        if let Some(block_id) = database.get_block_id_at_height(height).await? {
+            milestone_block_ids.push(block_id);
+        }
+        height = height.saturating_sub(jump);
+    }
+
+    Ok(HttpResponse::Ok().json(MilestoneBlockIdsResponse {
+        milestone_block_ids,
+        last: false,
+    }))
+}
@@ -0,0 +1,29 @@
+use actix_web::HttpResponse;
+use serde::Serialize;
+use serde_with::{serde_as, DisplayFromStr};
+
+use crate::{models::datastore::Datastore, srs_api::SignumApiError};
+
+/// The maximum number of block IDs returned in a single response.
+const MAX_NEXT_BLOCK_IDS: u32 = 100;
+
+#[serde_as]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NextBlockIdsResponse {
+    #[serde_as(as = "Vec<DisplayFromStr>")]
+    next_block_ids: Vec<u64>,
+}
+
+/// Returns the IDs of the blocks following `block_id` in our chain.
+#[tracing::instrument(skip(database))]
+pub(crate) async fn get_next_block_ids_handler(
+    block_id: u64,
+    database: &Datastore,
+) -> Result<HttpResponse, SignumApiError> {
+    let next_block_ids = database
+        .get_block_ids_after(block_id, MAX_NEXT_BLOCK_IDS)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(NextBlockIdsResponse { next_block_ids }))
+}
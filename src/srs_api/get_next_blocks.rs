@@ -0,0 +1,41 @@
+use actix_web::HttpResponse;
+use serde::Serialize;
+
+use crate::{
+    models::{datastore::Datastore, p2p::B1Block},
+    srs_api::SignumApiError,
+};
+
+/// The maximum number of blocks returned in a single response.
+const MAX_NEXT_BLOCKS: u32 = 100;
+/// The size of a block without its transactions, used to estimate the response size.
+const BLOCK_HEADER_LENGTH: u64 = 232;
+/// The maximum estimated size of the blocks returned in a single response.
+const MAX_RESPONSE_LENGTH: u64 = 1_048_576;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NextBlocksResponse {
+    next_blocks: Vec<B1Block>,
+}
+
+/// Returns the blocks following `block_id` in our chain, stopping early once the response
+/// would grow beyond roughly one megabyte.
+#[tracing::instrument(skip(database))]
+pub(crate) async fn get_next_blocks_handler(
+    block_id: u64,
+    database: &Datastore,
+) -> Result<HttpResponse, SignumApiError> {
+    let blocks = database.get_blocks_after(block_id, MAX_NEXT_BLOCKS).await?;
+
+    let mut total_length = 0;
+    let next_blocks = blocks
+        .into_iter()
+        .take_while(|block| {
+            total_length += BLOCK_HEADER_LENGTH + u64::from(block.payload_length);
+            total_length <= MAX_RESPONSE_LENGTH
+        })
+        .collect::<Vec<_>>();
+
+    Ok(HttpResponse::Ok().json(NextBlocksResponse { next_blocks }))
+}
@@ -1,5 +1,29 @@
+use std::time::Duration;
+
 use actix_web::HttpResponse;
+use serde::Serialize;
+
+use crate::{
+    configuration::PeerToPeerSettings,
+    models::{datastore::Datastore, p2p::PeerAddress},
+    srs_api::SignumApiError,
+};
+
+/// Peers that haven't been seen within this window are not handed out to other nodes.
+const PEER_SEEN_WITHIN: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Serialize)]
+struct PeerContainer {
+    peers: Vec<PeerAddress>,
+}
+
+pub(crate) async fn get_peers_handler(
+    database: &Datastore,
+    settings: &PeerToPeerSettings,
+) -> Result<HttpResponse, SignumApiError> {
+    let peers = database
+        .get_shareable_peers(PEER_SEEN_WITHIN, settings.max_shared_peers)
+        .await?;
 
-pub(crate) fn get_peers_handler() -> Result<HttpResponse, actix_web::Error> {
-    Ok(HttpResponse::Ok().json(vec!["127.0.0.1".to_string()]))
+    Ok(HttpResponse::Ok().json(PeerContainer { peers }))
 }
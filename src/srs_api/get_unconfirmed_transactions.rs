@@ -0,0 +1,31 @@
+use actix_web::HttpResponse;
+use serde::Serialize;
+
+use crate::{
+    models::{datastore::Datastore, p2p::B1Transaction},
+    signum_timestamp,
+    srs_api::SignumApiError,
+};
+
+/// The maximum number of unconfirmed transactions returned in a single response.
+const MAX_UNCONFIRMED_TRANSACTIONS: u32 = 8192;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UnconfirmedTransactionsResponse {
+    unconfirmed_transactions: Vec<B1Transaction>,
+}
+
+/// Returns the unexpired transactions in our unconfirmed transaction pool.
+#[tracing::instrument(skip_all)]
+pub(crate) async fn get_unconfirmed_transactions_handler(
+    database: &Datastore,
+) -> Result<HttpResponse, SignumApiError> {
+    let unconfirmed_transactions = database
+        .get_unconfirmed_transactions(signum_timestamp(), MAX_UNCONFIRMED_TRANSACTIONS)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(UnconfirmedTransactionsResponse {
+        unconfirmed_transactions,
+    }))
+}
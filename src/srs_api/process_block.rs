@@ -0,0 +1,162 @@
+use actix_web::HttpResponse;
+use serde::Serialize;
+
+use anyhow::Context;
+
+use crate::{
+    models::{
+        datastore::{Datastore, DatastoreError},
+        p2p::{B1Block, TransactionId},
+        Block,
+    },
+    poc,
+    srs_api::SignumApiError,
+    validation::{validate_base_target, validate_block, BlockValidationError},
+    Active, HistoricalMoments,
+};
+
+#[derive(Debug, Serialize)]
+struct ProcessBlockResponse {
+    accepted: bool,
+}
+
+/// Accepts a block pushed by a peer if it extends our chain tip and passes validation.
+///
+/// Blocks that don't build on our tip are ignored, as they are usually the result of the
+/// peer being ahead of us, and so are blocks past a fork whose rules we can't check yet.
+/// Blocks that fail validation get the sending peer blacklisted, unless it's our side that
+/// can't finish checking them, such as when we lack the blocks before our tip.
+#[tracing::instrument(skip_all)]
+pub(crate) async fn process_block_handler(
+    block: B1Block,
+    remote_ip: Option<String>,
+    database: &Datastore,
+    historical_moments: &HistoricalMoments<Active>,
+) -> Result<HttpResponse, SignumApiError> {
+    let Some((last_block_id, height, previous_cumulative_difficulty)) =
+        database.get_chain_tip().await?
+    else {
+        tracing::debug!("Ignoring pushed block, we have no chain yet");
+        return Ok(not_accepted());
+    };
+
+    if block.previous_block != last_block_id {
+        tracing::debug!(
+            "Ignoring pushed block, its previous block {} is not our tip {}",
+            block.previous_block,
+            last_block_id
+        );
+        return Ok(not_accepted());
+    }
+
+    // The tip and the blocks before it, which the base target is averaged over
+    let recent_blocks = database
+        .get_blocks_from_height(height.saturating_sub(23), 24)
+        .await?
+        .into_iter()
+        .zip(height.saturating_sub(23)..)
+        .map(|(b, h)| {
+            Block::try_from(b).map(|mut b| {
+                b.height = Some(h);
+                b
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .context("stored chain is malformed")?;
+    let previous_block = recent_blocks
+        .last()
+        .ok_or_else(|| anyhow::anyhow!("chain tip {} could not be loaded", last_block_id))?;
+
+    let validation = match Block::try_from(block.clone()) {
+        Ok(mut b) => {
+            b.height = Some(height + 1);
+            // Pool miners' blocks are signed by the pool they assigned their rewards to
+            let signer_public_key = database
+                .get_block_signer_public_key(&b.generator_public_key, height + 1)
+                .await?;
+            validate_block(
+                &b,
+                previous_block,
+                signer_public_key.as_ref(),
+                historical_moments,
+            )
+            .and_then(|()| validate_base_target(&b, &recent_blocks, historical_moments))
+            .map(|()| b)
+        }
+        Err(e) => Err(e.into()),
+    };
+    let validation = match validation {
+        Ok(b) => match find_confirmed_transaction(&b, database).await? {
+            Some(id) => Err(BlockValidationError::TransactionAlreadyConfirmed(id)),
+            None => Ok(b),
+        },
+        Err(e) => Err(e),
+    };
+    let (block_id, base_target) = match validation {
+        Ok(b) => (b.id, b.base_target),
+        Err(e @ BlockValidationError::UnsupportedFork { .. }) => {
+            tracing::info!("Not accepting pushed block: {}", e);
+            return Ok(not_accepted());
+        }
+        Err(BlockValidationError::UnexpectedError(e)) => {
+            return Err(e.context("unable to validate pushed block").into())
+        }
+        Err(e) => {
+            tracing::warn!("Received an invalid block: {}", e);
+            if let Some(ip) = remote_ip {
+                if let Some(peer) = database.get_peer_address_by_ip(&ip).await? {
+                    tracing::debug!("Blacklisting {} for sending an invalid block", &peer);
+                    database.blacklist_peer(&peer).await?;
+                }
+            }
+            return Ok(not_accepted());
+        }
+    };
+
+    // Our tip is the previous block, so its cumulative difficulty is the one to build on
+    let cumulative_difficulty =
+        poc::calculate_cumulative_difficulty(&previous_cumulative_difficulty, base_target);
+    match database
+        .store_block(&block, block_id.into(), height + 1, &cumulative_difficulty)
+        .await
+    {
+        Ok(_) => {}
+        // Another block, likely the same one pushed by another peer, got there first
+        Err(DatastoreError::HeightTaken(_)) => {
+            tracing::debug!("Ignoring pushed block, our tip moved on while validating it");
+            return Ok(not_accepted());
+        }
+        Err(e) => return Err(e.into()),
+    }
+    tracing::info!(
+        "Accepted pushed block {} at height {}",
+        block_id,
+        height + 1
+    );
+
+    Ok(HttpResponse::Ok().json(ProcessBlockResponse { accepted: true }))
+}
+
+/// Returns the first of the block's transactions that is already in the local chain, if any.
+async fn find_confirmed_transaction(
+    block: &Block,
+    database: &Datastore,
+) -> Result<Option<TransactionId>, DatastoreError> {
+    let transaction_ids = block
+        .transactions
+        .iter()
+        .map(|t| t.id.into())
+        .collect::<Vec<u64>>();
+    let confirmed = database
+        .get_confirmed_transaction_ids(&transaction_ids)
+        .await?;
+    Ok(block
+        .transactions
+        .iter()
+        .map(|t| t.id)
+        .find(|id| confirmed.contains(&u64::from(*id))))
+}
+
+fn not_accepted() -> HttpResponse {
+    HttpResponse::Ok().json(ProcessBlockResponse { accepted: false })
+}
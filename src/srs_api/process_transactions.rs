@@ -0,0 +1,49 @@
+use actix_web::HttpResponse;
+use serde_json::json;
+
+use crate::{
+    models::{datastore::Datastore, p2p::B1Transaction},
+    srs_api::SignumApiError,
+    validation::validate_unconfirmed_transaction,
+    Active, HistoricalMoments,
+};
+
+/// Adds transactions pushed by a peer to the unconfirmed transaction pool.
+///
+/// Transactions already in the pool are skipped. If any transaction fails validation, the
+/// sending peer is blacklisted and the remaining transactions are ignored.
+#[tracing::instrument(skip_all)]
+pub(crate) async fn process_transactions_handler(
+    transactions: Vec<B1Transaction>,
+    remote_ip: Option<String>,
+    database: &Datastore,
+    historical_moments: &HistoricalMoments<Active>,
+) -> Result<HttpResponse, SignumApiError> {
+    let next_height = database
+        .get_chain_tip()
+        .await?
+        .map_or(0, |(_, height, _)| height + 1);
+
+    let mut new_transactions = 0;
+    for transaction in transactions {
+        if let Err(e) =
+            validate_unconfirmed_transaction(&transaction, next_height, historical_moments)
+        {
+            tracing::warn!("Received an invalid transaction: {}", e);
+            if let Some(ip) = remote_ip {
+                if let Some(peer) = database.get_peer_address_by_ip(&ip).await? {
+                    tracing::debug!("Blacklisting {} for sending an invalid transaction", &peer);
+                    database.blacklist_peer(&peer).await?;
+                }
+            }
+            break;
+        }
+
+        if database.add_unconfirmed_transaction(&transaction).await? {
+            new_transactions += 1;
+        }
+    }
+    tracing::debug!("Added {} new unconfirmed transactions", new_transactions);
+
+    Ok(HttpResponse::Ok().json(json!({})))
+}
@@ -1,16 +1,61 @@
 use serde::Deserialize;
+use serde_with::{serde_as, DisplayFromStr, PickFirst};
+
+use crate::models::p2p::{B1Block, B1Transaction};
 
 /// Represents each of the types of request that can be made to the SRS Peer to Peer API.
-/// Currently ignores the 'protocol' field, since that is always `B1` and has never changed.
-/// May need to include that later if SRS changes.
+/// Ignores the 'protocol' field, which is checked by the handler before deserializing.
+#[serde_as]
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 #[serde(rename_all_fields = "camelCase")]
 #[serde(tag = "requestType")]
 pub enum RequestType {
-    AddPeers { peers: Vec<String> },
+    AddPeers {
+        peers: Vec<String>,
+    },
+    GetBlocksFromHeight {
+        #[serde_as(as = "PickFirst<(_, DisplayFromStr)>")]
+        height: u64,
+        #[serde_as(as = "Option<PickFirst<(_, DisplayFromStr)>>")]
+        #[serde(default)]
+        num_blocks: Option<u32>,
+    },
+    GetCumulativeDifficulty {},
     GetInfo(GetInfoRequestModel),
+    GetMilestoneBlockIds(GetMilestoneBlockIdsRequestModel),
+    GetNextBlockIds {
+        #[serde_as(as = "PickFirst<(DisplayFromStr, _)>")]
+        block_id: u64,
+    },
+    GetNextBlocks {
+        #[serde_as(as = "PickFirst<(DisplayFromStr, _)>")]
+        block_id: u64,
+    },
     GetPeers {},
+    GetUnconfirmedTransactions {},
+    ProcessBlock(B1Block),
+    ProcessTransactions {
+        transactions: Vec<B1Transaction>,
+    },
+}
+
+/// The `requestType` tags of [`RequestType`], which are checked on their own first so an unknown
+/// request type can be told apart from a malformed request.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RequestTypeTag {
+    AddPeers,
+    GetBlocksFromHeight,
+    GetCumulativeDifficulty,
+    GetInfo,
+    GetMilestoneBlockIds,
+    GetNextBlockIds,
+    GetNextBlocks,
+    GetPeers,
+    GetUnconfirmedTransactions,
+    ProcessBlock,
+    ProcessTransactions,
 }
 
 #[derive(Debug, Deserialize, PartialEq)]
@@ -23,3 +68,16 @@ pub struct GetInfoRequestModel {
     pub share_address: Option<bool>,
     pub network_name: String,
 }
+
+/// Block IDs are normally sent as strings, but some peers send them as bare numbers.
+#[serde_as]
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GetMilestoneBlockIdsRequestModel {
+    #[serde_as(as = "Option<PickFirst<(DisplayFromStr, _)>>")]
+    #[serde(default)]
+    pub last_block_id: Option<u64>,
+    #[serde_as(as = "Option<PickFirst<(DisplayFromStr, _)>>")]
+    #[serde(default)]
+    pub last_milestone_block_id: Option<u64>,
+}
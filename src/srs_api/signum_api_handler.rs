@@ -1,28 +1,175 @@
 use actix_web::{
+    http::StatusCode,
     web::{Data, Json},
-    Responder,
+    HttpRequest, HttpResponse, ResponseError,
 };
+use serde_json::{json, Value};
 
 use crate::{
     configuration::PeerToPeerSettings,
-    srs_api::{add_peers, get_info, get_peers},
+    models::datastore::{Datastore, DatastoreError},
+    srs_api::{
+        add_peers, get_blocks_from_height, get_cumulative_difficulty, get_info,
+        get_milestone_block_ids, get_next_block_ids, get_next_blocks, get_peers,
+        get_unconfirmed_transactions, outgoing_json::OutgoingRequestError, process_block,
+        process_transactions,
+    },
+    Active, HistoricalMoments,
 };
 
 use super::request_models;
 
+/// The only protocol spoken on the SRS Peer to Peer API.
+const PROTOCOL: &str = "B1";
+
 #[tracing::instrument(skip_all)]
 pub async fn signum_api_handler(
+    request: HttpRequest,
     settings: Data<PeerToPeerSettings>,
-    request_object: Json<request_models::RequestType>,
-) -> Result<impl Responder, actix_web::Error> {
+    database: Data<Datastore>,
+    historical_moments: Data<HistoricalMoments<Active>>,
+    request_object: Json<Value>,
+) -> Result<HttpResponse, SignumApiError> {
     tracing::debug!("Request Object: {:#?}", &request_object);
     let settings = settings.into_inner();
+    let database = database.into_inner();
+
+    let request_object = parse_request(request_object.into_inner())?;
+
+    let remote_ip = request.peer_addr().map(|a| a.ip().to_string());
 
-    match request_object.0 {
-        request_models::RequestType::AddPeers { peers } => add_peers::add_peers_handler(peers),
+    match request_object {
+        request_models::RequestType::AddPeers { peers } => {
+            add_peers::add_peers_handler(&database, peers).await
+        }
+        request_models::RequestType::GetBlocksFromHeight { height, num_blocks } => {
+            get_blocks_from_height::get_blocks_from_height_handler(height, num_blocks, &database)
+                .await
+        }
+        request_models::RequestType::GetCumulativeDifficulty {} => {
+            get_cumulative_difficulty::get_cumulative_difficulty_handler(&database).await
+        }
         request_models::RequestType::GetInfo(payload) => {
-            get_info::get_info_handler(payload, &settings)
+            get_info::get_info_handler(payload, remote_ip, &database, &settings).await
+        }
+        request_models::RequestType::GetMilestoneBlockIds(payload) => {
+            get_milestone_block_ids::get_milestone_block_ids_handler(payload, &database).await
+        }
+        request_models::RequestType::GetNextBlockIds { block_id } => {
+            get_next_block_ids::get_next_block_ids_handler(block_id, &database).await
+        }
+        request_models::RequestType::GetNextBlocks { block_id } => {
+            get_next_blocks::get_next_blocks_handler(block_id, &database).await
         }
-        request_models::RequestType::GetPeers {} => get_peers::get_peers_handler(),
+        request_models::RequestType::GetPeers {} => {
+            get_peers::get_peers_handler(&database, &settings).await
+        }
+        request_models::RequestType::GetUnconfirmedTransactions {} => {
+            get_unconfirmed_transactions::get_unconfirmed_transactions_handler(&database).await
+        }
+        request_models::RequestType::ProcessBlock(block) => {
+            process_block::process_block_handler(block, remote_ip, &database, &historical_moments)
+                .await
+        }
+        request_models::RequestType::ProcessTransactions { transactions } => {
+            process_transactions::process_transactions_handler(
+                transactions,
+                remote_ip,
+                &database,
+                &historical_moments,
+            )
+            .await
+        }
+    }
+}
+
+/// Checks the protocol and request type of a raw request before deserializing it, so that
+/// each problem gets the error a BRS peer would answer with.
+///
+/// A missing `protocol` is accepted, since older peers don't always send it.
+fn parse_request(request: Value) -> Result<request_models::RequestType, SignumApiError> {
+    match request.get("protocol") {
+        None => {}
+        Some(Value::String(protocol)) if protocol == PROTOCOL => {}
+        Some(_) => return Err(SignumApiError::UnsupportedProtocol),
+    }
+
+    let request_type = request.get("requestType").cloned().unwrap_or_default();
+    if serde_json::from_value::<request_models::RequestTypeTag>(request_type).is_err() {
+        return Err(SignumApiError::UnsupportedRequestType);
+    }
+
+    serde_json::from_value(request).map_err(|e| SignumApiError::InvalidPayload(e.to_string()))
+}
+
+/// Represents the errors returned by the SRS Peer to Peer API.
+///
+/// These are answered with a BRS-style `{"error": "..."}` JSON body.
+#[derive(thiserror::Error)]
+pub enum SignumApiError {
+    #[error("Unsupported protocol!")]
+    UnsupportedProtocol,
+    #[error("Unsupported request type!")]
+    UnsupportedRequestType,
+    #[error("Invalid request payload: {0}")]
+    InvalidPayload(String),
+    #[error(transparent)]
+    DatastoreError(#[from] DatastoreError),
+    #[error(transparent)]
+    OutgoingRequestError(#[from] OutgoingRequestError),
+    #[error(transparent)]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl ResponseError for SignumApiError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            SignumApiError::UnsupportedProtocol
+            | SignumApiError::UnsupportedRequestType
+            | SignumApiError::InvalidPayload(_) => StatusCode::BAD_REQUEST,
+            SignumApiError::DatastoreError(_)
+            | SignumApiError::OutgoingRequestError(_)
+            | SignumApiError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let message = match self {
+            // Don't leak internal details to peers, those go to the logs
+            SignumApiError::DatastoreError(_)
+            | SignumApiError::OutgoingRequestError(_)
+            | SignumApiError::UnexpectedError(_) => {
+                tracing::error!("Unable to process request: {:?}", self);
+                "Internal error!".to_string()
+            }
+            _ => self.to_string(),
+        };
+        HttpResponse::build(self.status_code()).json(json!({ "error": message }))
+    }
+}
+
+impl std::fmt::Debug for SignumApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        crate::error_chain_fmt(self, f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::{parse_request, SignumApiError};
+
+    #[test]
+    fn parse_request_tells_unknown_request_types_from_invalid_payloads() {
+        assert!(matches!(
+            parse_request(json!({ "requestType": "getAccount" })),
+            Err(SignumApiError::UnsupportedRequestType)
+        ));
+        assert!(matches!(
+            parse_request(json!({ "requestType": "getNextBlocks" })),
+            Err(SignumApiError::InvalidPayload(_))
+        ));
+        assert!(parse_request(json!({ "requestType": "getPeers" })).is_ok());
     }
 }
@@ -0,0 +1,990 @@
+//! Validation rules that blocks and transactions received from peers must pass before they are
+//! added to the chain or the unconfirmed transaction pool.
+
+use sha2::{Digest, Sha256};
+
+use crate::{
+    flux_capacitor::{MAX_NUMBER_OF_TRANSACTIONS, MAX_PAYLOAD_LENGTH, MIN_FEE_NQT},
+    models::{
+        p2p::{
+            B1Transaction, BlockId, Transaction, TransactionConversionError, TransactionId,
+            TransactionType,
+        },
+        Amount, Block, BlockConversionError,
+    },
+    poc, signum_timestamp, Active, Fork, HistoricalMoments,
+};
+
+/// How many seconds ahead of our clock a block or transaction timestamp may be.
+const MAX_TIMESTAMP_DRIFT: u64 = 15;
+
+/// The longest deadline a transaction may have, in minutes.
+const MAX_DEADLINE_MINUTES: u16 = 1440;
+
+/// Validates `block` as the successor of `previous_block`.
+///
+/// The block's height must be set, as the rules it's checked against depend on it. Blocks
+/// from the PoC+ fork on are rejected with [`BlockValidationError::UnsupportedFork`] before
+/// anything else is checked, as their deadline can't be checked yet and the other rules may
+/// have changed with the fork too.
+///
+/// `signer_public_key` is the key the block must be signed with, as returned by
+/// [`Datastore::get_block_signer_public_key`]. Like BRS, a block fails if the signer's public
+/// key isn't known.
+///
+/// [`Datastore::get_block_signer_public_key`]: crate::models::datastore::Datastore::get_block_signer_public_key
+#[tracing::instrument(skip_all)]
+pub fn validate_block(
+    block: &Block,
+    previous_block: &Block,
+    signer_public_key: Option<&[u8; 32]>,
+    historical_moments: &HistoricalMoments<Active>,
+) -> Result<(), BlockValidationError> {
+    let height = block.height.ok_or(BlockValidationError::MissingHeight)?;
+
+    if let Some(fork) = unsupported_fork(height, historical_moments) {
+        return Err(BlockValidationError::UnsupportedFork { fork, height });
+    }
+
+    if block.previous_block_id != previous_block.id {
+        return Err(BlockValidationError::PreviousBlockIdMismatch {
+            expected: previous_block.id,
+            actual: block.previous_block_id,
+        });
+    }
+
+    if block.version > 1 && block.previous_block_hash != Some(previous_block.full_hash()) {
+        return Err(BlockValidationError::PreviousBlockHashMismatch);
+    }
+
+    if block.timestamp <= previous_block.timestamp {
+        return Err(BlockValidationError::TimestampNotAfterPrevious {
+            timestamp: block.timestamp,
+            previous_timestamp: previous_block.timestamp,
+        });
+    }
+
+    if block.timestamp > signum_timestamp() + MAX_TIMESTAMP_DRIFT {
+        return Err(BlockValidationError::TimestampInFuture(block.timestamp));
+    }
+
+    if block.generation_signature != poc::calculate_generation_signature(previous_block) {
+        return Err(BlockValidationError::GenerationSignatureMismatch);
+    }
+
+    validate_block_payload(block, historical_moments)?;
+
+    let deadline = poc::block_deadline(block, previous_block, historical_moments)
+        .ok_or(BlockValidationError::MissingHeight)?;
+    let elapsed = block.timestamp - previous_block.timestamp;
+    if deadline >= elapsed {
+        return Err(BlockValidationError::DeadlineNotElapsed { deadline, elapsed });
+    }
+
+    if !signer_public_key.is_some_and(|key| block.verify_signature(key)) {
+        return Err(BlockValidationError::InvalidBlockSignature);
+    }
+
+    verify_transaction_signatures(block)
+}
+
+/// Validates the transactions a block carries against its totals and payload, and against the
+/// limits at its height.
+///
+/// This is the part of [`validate_block`] that doesn't depend on the previous block.
+#[tracing::instrument(skip_all)]
+pub fn validate_block_payload(
+    block: &Block,
+    historical_moments: &HistoricalMoments<Active>,
+) -> Result<(), BlockValidationError> {
+    let height = block.height.ok_or(BlockValidationError::MissingHeight)?;
+
+    let max_transactions = MAX_NUMBER_OF_TRANSACTIONS.get_flux_value(height, historical_moments);
+    if block.transactions.len() > max_transactions as usize {
+        return Err(BlockValidationError::TooManyTransactions {
+            count: block.transactions.len(),
+            max: max_transactions,
+        });
+    }
+
+    let max_payload_length = MAX_PAYLOAD_LENGTH.get_flux_value(height, historical_moments);
+    if block.payload_length > max_payload_length {
+        return Err(BlockValidationError::PayloadTooLong {
+            length: block.payload_length,
+            max: max_payload_length,
+        });
+    }
+
+    // The totals also count what the block's ATs pay out, so the transactions can add up to less
+    let total_amount = block.transactions.iter().map(|t| t.amount).sum::<Amount>();
+    if total_amount > block.total_amount {
+        return Err(BlockValidationError::TotalAmountExceeded {
+            total: block.total_amount,
+            transactions: total_amount,
+        });
+    }
+
+    let total_fee = block.transactions.iter().map(|t| t.fee).sum::<Amount>();
+    if total_fee > block.total_fee {
+        return Err(BlockValidationError::TotalFeeExceeded {
+            total: block.total_fee,
+            transactions: total_fee,
+        });
+    }
+
+    if let Some(transaction) = block
+        .transactions
+        .iter()
+        .find(|t| !t.transaction_type.is_enabled_at(height, historical_moments))
+    {
+        return Err(BlockValidationError::TransactionTypeNotEnabled {
+            transaction_type: transaction.transaction_type,
+            height,
+        });
+    }
+    let payload = block
+        .transactions
+        .iter()
+        .flat_map(Transaction::to_bytes)
+        .collect::<Vec<_>>();
+    // The payload length also counts the AT state the block carries, the hash doesn't
+    let payload_length = payload.len() + block.block_ats.as_ref().map_or(0, Vec::len);
+    if payload_length != block.payload_length as usize {
+        return Err(BlockValidationError::PayloadLengthMismatch {
+            expected: block.payload_length,
+            actual: payload_length,
+        });
+    }
+    if Sha256::digest(&payload)[..] != block.payload_hash {
+        return Err(BlockValidationError::PayloadHashMismatch);
+    }
+
+    Ok(())
+}
+
+/// Validates that `block` has the base target the difficulty adjustment gives it.
+///
+/// `recent_blocks` are the blocks before it, oldest first, ending with its previous block.
+/// They need to reach back 24 blocks, or to the genesis block on shorter chains.
+///
+/// Blocks from the PoC+ fork on are rejected with [`BlockValidationError::UnsupportedFork`],
+/// as their base target can't be checked yet.
+#[tracing::instrument(skip_all)]
+pub fn validate_base_target(
+    block: &Block,
+    recent_blocks: &[Block],
+    historical_moments: &HistoricalMoments<Active>,
+) -> Result<(), BlockValidationError> {
+    let height = block.height.ok_or(BlockValidationError::MissingHeight)?;
+
+    if let Some(fork) = unsupported_fork(height, historical_moments) {
+        return Err(BlockValidationError::UnsupportedFork { fork, height });
+    }
+
+    let expected =
+        poc::calculate_base_target(height, block.timestamp, recent_blocks, historical_moments)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "the blocks before height {} are needed to check its base target",
+                    height
+                )
+            })?;
+    if block.base_target != expected {
+        return Err(BlockValidationError::BaseTargetMismatch {
+            expected,
+            actual: block.base_target,
+        });
+    }
+
+    Ok(())
+}
+
+/// Returns the latest fork active at `height` whose consensus rules aren't implemented yet.
+///
+/// PoC+ packs the generators' average commitment into the base target and scales deadlines
+/// by the generator's own commitment, which needs account balances the node doesn't track
+/// yet. Speedway changes the difficulty adjustment again on top of that.
+fn unsupported_fork(height: u64, historical_moments: &HistoricalMoments<Active>) -> Option<Fork> {
+    [Fork::SpeedwayEnable, Fork::PocPlusEnable]
+        .into_iter()
+        .find(|fork| historical_moments.is_active(*fork, height))
+}
+
+/// Verifies the signatures of all of a block's transactions.
+///
+/// The block's own signature is checked by [`validate_block`], as who signs it depends on the
+/// chain before it.
+#[tracing::instrument(skip_all)]
+pub fn verify_transaction_signatures(block: &Block) -> Result<(), BlockValidationError> {
+    for transaction in &block.transactions {
+        if !transaction.verify_signature() {
+            return Err(BlockValidationError::InvalidTransactionSignature(
+                transaction.id,
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Represents the reasons a block can fail validation.
+#[derive(thiserror::Error)]
+pub enum BlockValidationError {
+    #[error("block is malformed: {0}")]
+    MalformedBlock(#[from] BlockConversionError),
+    #[error("block height is unknown")]
+    MissingHeight,
+    #[error("block builds on {actual} instead of the previous block {expected}")]
+    PreviousBlockIdMismatch { expected: BlockId, actual: BlockId },
+    #[error("block's previous block hash doesn't match the previous block")]
+    PreviousBlockHashMismatch,
+    #[error("block timestamp {timestamp} is not after the previous block's {previous_timestamp}")]
+    TimestampNotAfterPrevious {
+        timestamp: u64,
+        previous_timestamp: u64,
+    },
+    #[error("block timestamp {0} is too far in the future")]
+    TimestampInFuture(u64),
+    #[error("block's generation signature doesn't follow from the previous block")]
+    GenerationSignatureMismatch,
+    #[error("block base target should be {expected} but was {actual}")]
+    BaseTargetMismatch { expected: u64, actual: u64 },
+    #[error("block at height {height} is past the {fork:?} fork, which can't be checked yet")]
+    UnsupportedFork { fork: Fork, height: u64 },
+    #[error("block transactions add up to {transactions}, more than its total amount of {total}")]
+    TotalAmountExceeded { total: Amount, transactions: Amount },
+    #[error("block transactions add up to {transactions}, more than its total fee of {total}")]
+    TotalFeeExceeded { total: Amount, transactions: Amount },
+    #[error("block has {count} transactions, more than the maximum of {max}")]
+    TooManyTransactions { count: usize, max: u32 },
+    #[error("block payload length of {length} bytes is more than the maximum of {max}")]
+    PayloadTooLong { length: u32, max: u32 },
+    #[error("block payload length is {expected} but its transactions and ATs take {actual} bytes")]
+    PayloadLengthMismatch { expected: u32, actual: usize },
+    #[error("block payload hash doesn't match its transactions")]
+    PayloadHashMismatch,
+    #[error(
+        "block was forged {elapsed}s after the previous block, before its deadline of {deadline}s"
+    )]
+    DeadlineNotElapsed { deadline: u64, elapsed: u64 },
+    #[error("block is not signed by its generator or reward recipient")]
+    InvalidBlockSignature,
+    #[error("block has transaction {0}, which is not signed by its sender")]
+    InvalidTransactionSignature(TransactionId),
+    #[error("block has transaction {0}, which is already in the chain")]
+    TransactionAlreadyConfirmed(TransactionId),
+    #[error(
+        "block has a {transaction_type:?} transaction, which isn't enabled at height {height}"
+    )]
+    TransactionTypeNotEnabled {
+        transaction_type: TransactionType,
+        height: u64,
+    },
+    #[error(transparent)]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for BlockValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        crate::error_chain_fmt(self, f)
+    }
+}
+
+/// Validates a transaction before it is added to the unconfirmed transaction pool.
+///
+/// Checks the deadline, fee, the shape of the sender public key and the signature.
+///
+/// `next_height` is the height of the next block on our chain, or 0 without a chain. The
+/// fee is checked at the later of it and the transaction's economic clustering block
+/// height, which the network has reached even if we're still catching up.
+#[tracing::instrument(skip_all)]
+pub fn validate_unconfirmed_transaction(
+    transaction: &B1Transaction,
+    next_height: u64,
+    historical_moments: &HistoricalMoments<Active>,
+) -> Result<(), TransactionValidationError> {
+    if transaction.deadline == 0 || transaction.deadline > MAX_DEADLINE_MINUTES {
+        return Err(TransactionValidationError::InvalidDeadline(
+            transaction.deadline,
+        ));
+    }
+
+    let now = signum_timestamp();
+    if transaction.timestamp > now + MAX_TIMESTAMP_DRIFT {
+        return Err(TransactionValidationError::TimestampInFuture(
+            transaction.timestamp,
+        ));
+    }
+
+    if transaction.expiration() <= now {
+        return Err(TransactionValidationError::Expired(
+            transaction.expiration(),
+        ));
+    }
+
+    let height = next_height.max(u64::from(transaction.ec_block_height));
+    if transaction.fee_nqt < MIN_FEE_NQT.get_flux_value(height, historical_moments) {
+        return Err(TransactionValidationError::FeeTooLow(transaction.fee_nqt));
+    }
+
+    if !matches!(hex::decode(&transaction.sender_public_key), Ok(k) if k.len() == 32) {
+        return Err(TransactionValidationError::MalformedSenderPublicKey);
+    }
+
+    match hex::decode(&transaction.signature) {
+        Ok(s) if s.len() == 64 && s.iter().any(|b| *b != 0) => {}
+        _ => return Err(TransactionValidationError::MalformedSignature),
+    }
+
+    if !Transaction::try_from(transaction.clone())?.verify_signature() {
+        return Err(TransactionValidationError::InvalidSignature);
+    }
+
+    Ok(())
+}
+
+/// Represents the reasons a transaction can fail validation.
+#[derive(thiserror::Error)]
+pub enum TransactionValidationError {
+    #[error("transaction deadline of {0} minutes is out of range")]
+    InvalidDeadline(u16),
+    #[error("transaction timestamp {0} is too far in the future")]
+    TimestampInFuture(u64),
+    #[error("transaction expired at {0}")]
+    Expired(u64),
+    #[error("transaction fee of {0} NQT is below the minimum")]
+    FeeTooLow(u64),
+    #[error("transaction sender public key is not 32 hex encoded bytes")]
+    MalformedSenderPublicKey,
+    #[error("transaction signature is not 64 hex encoded bytes")]
+    MalformedSignature,
+    #[error("transaction is malformed: {0}")]
+    MalformedTransaction(#[from] TransactionConversionError),
+    #[error("transaction is not signed by its sender")]
+    InvalidSignature,
+}
+
+impl std::fmt::Debug for TransactionValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        crate::error_chain_fmt(self, f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use sha2::{Digest, Sha256};
+
+    use crate::{
+        crypto,
+        models::{
+            p2p::{B1Block, B1Transaction, BlockId, Transaction, TransactionType},
+            Amount, Block,
+        },
+        poc, signum_timestamp,
+        validation::{
+            validate_base_target, validate_block, validate_block_payload,
+            validate_unconfirmed_transaction, BlockValidationError, TransactionValidationError,
+        },
+        Active, Fork, HistoricalMoments,
+    };
+
+    /// The passphrase test blocks and transactions are signed with.
+    const PASSPHRASE: &str = "validation test passphrase";
+
+    /// Mainnet's historical moments without the forks blocks can't be checked against yet,
+    /// so the fixture block can be validated in full.
+    fn historical_moments() -> HistoricalMoments<Active> {
+        HistoricalMoments::new()
+            .with_height(Fork::PocPlusEnable, 2_000_000)
+            .with_height(Fork::SpeedwayEnable, 2_000_000)
+            .build()
+    }
+
+    /// Returns the public key test blocks are signed with.
+    fn signer() -> [u8; 32] {
+        crypto::public_key(PASSPHRASE)
+    }
+
+    fn fixture_block() -> Block {
+        Block::try_from(
+            serde_json::from_str::<B1Block>(include_str!("../tests/fixtures/b1_block.json"))
+                .unwrap(),
+        )
+        .unwrap()
+    }
+
+    fn previous_block() -> Block {
+        let mut previous_block = fixture_block();
+        previous_block.timestamp -= 240;
+        previous_block.id = BlockId::from_full_hash(&previous_block.full_hash());
+        previous_block
+    }
+
+    /// Returns the fixture block on top of [`previous_block`].
+    fn block() -> Block {
+        let mut block = fixture_block();
+        // The fixture is a mainnet block from after the smart token fork
+        block.height = Some(1_200_000);
+        let previous_block = previous_block();
+        block.previous_block_id = previous_block.id;
+        block.previous_block_hash = Some(previous_block.full_hash());
+        block.generation_signature = poc::calculate_generation_signature(&previous_block);
+        block.generator_public_key = crypto::public_key(PASSPHRASE);
+        block.block_signature = crypto::sign(&block.unsigned_bytes(), PASSPHRASE);
+        block
+    }
+
+    #[test]
+    fn validate_block_succeeds_for_valid_successor() {
+        validate_block(
+            &block(),
+            &previous_block(),
+            Some(&signer()),
+            &historical_moments(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn validate_block_fails_for_unlinked_previous_block() {
+        let mut block = block();
+        block.previous_block_id = BlockId::from(1);
+
+        let result = validate_block(
+            &block,
+            &previous_block(),
+            Some(&signer()),
+            &historical_moments(),
+        );
+        assert!(matches!(
+            result,
+            Err(BlockValidationError::PreviousBlockIdMismatch { .. })
+        ));
+
+        let mut block = self::block();
+        block.previous_block_hash = Some([0; 32]);
+
+        let result = validate_block(
+            &block,
+            &previous_block(),
+            Some(&signer()),
+            &historical_moments(),
+        );
+        assert!(matches!(
+            result,
+            Err(BlockValidationError::PreviousBlockHashMismatch)
+        ));
+    }
+
+    #[test]
+    fn validate_block_fails_for_timestamp_not_after_previous() {
+        let mut block = block();
+        block.timestamp = previous_block().timestamp;
+
+        let result = validate_block(
+            &block,
+            &previous_block(),
+            Some(&signer()),
+            &historical_moments(),
+        );
+        assert!(matches!(
+            result,
+            Err(BlockValidationError::TimestampNotAfterPrevious { .. })
+        ));
+    }
+
+    #[test]
+    fn validate_block_fails_for_timestamp_in_future() {
+        let mut block = block();
+        block.timestamp = u32::MAX.into();
+
+        let result = validate_block(
+            &block,
+            &previous_block(),
+            Some(&signer()),
+            &historical_moments(),
+        );
+        assert!(matches!(
+            result,
+            Err(BlockValidationError::TimestampInFuture(_))
+        ));
+    }
+
+    #[test]
+    fn validate_block_payload_succeeds_for_fixture_block() {
+        // The fixture's totals also count its AT payments and fees, so they are more than its
+        // transactions add up to
+        let mut block = fixture_block();
+        block.height = Some(1_200_000);
+        assert_eq!(block.total_amount, Amount::from_nqt(20_995_342_056));
+        assert_eq!(block.total_fee, Amount::from_nqt(171_800_000));
+
+        validate_block_payload(&block, &historical_moments()).unwrap();
+    }
+
+    #[test]
+    fn validate_block_fails_for_transactions_over_the_totals() {
+        let mut block = block();
+        let total_amount = block.transactions.iter().map(|t| t.amount).sum::<Amount>();
+        block.total_amount = Amount::from_nqt(total_amount.nqt() - 1);
+
+        let result = validate_block(
+            &block,
+            &previous_block(),
+            Some(&signer()),
+            &historical_moments(),
+        );
+        assert!(matches!(
+            result,
+            Err(BlockValidationError::TotalAmountExceeded { .. })
+        ));
+
+        let mut block = self::block();
+        let total_fee = block.transactions.iter().map(|t| t.fee).sum::<Amount>();
+        block.total_fee = Amount::from_nqt(total_fee.nqt() - 1);
+
+        let result = validate_block(
+            &block,
+            &previous_block(),
+            Some(&signer()),
+            &historical_moments(),
+        );
+        assert!(matches!(
+            result,
+            Err(BlockValidationError::TotalFeeExceeded { .. })
+        ));
+    }
+
+    #[test]
+    fn validate_block_fails_for_blocks_over_the_size_limits_at_height() {
+        let mut block = block();
+        let transaction = block.transactions[0].clone();
+        block.transactions = vec![transaction; 2_041];
+
+        let result = validate_block(
+            &block,
+            &previous_block(),
+            Some(&signer()),
+            &historical_moments(),
+        );
+        assert!(matches!(
+            result,
+            Err(BlockValidationError::TooManyTransactions {
+                count: 2_041,
+                max: 2_040
+            })
+        ));
+
+        let mut block = self::block();
+        block.payload_length = 375_361;
+
+        let result = validate_block(
+            &block,
+            &previous_block(),
+            Some(&signer()),
+            &historical_moments(),
+        );
+        assert!(matches!(
+            result,
+            Err(BlockValidationError::PayloadTooLong {
+                length: 375_361,
+                max: 375_360
+            })
+        ));
+    }
+
+    #[test]
+    fn validate_block_fails_for_transaction_type_not_enabled_at_height() {
+        let mut block = block();
+        // The fixture carries a multi-out transaction, which came with the pre-PoC2 fork
+        block.height = Some(1);
+
+        let result = validate_block(
+            &block,
+            &previous_block(),
+            Some(&signer()),
+            &historical_moments(),
+        );
+        assert!(matches!(
+            result,
+            Err(BlockValidationError::TransactionTypeNotEnabled {
+                transaction_type: TransactionType::MultiOut,
+                height: 1
+            })
+        ));
+
+        let mut block = self::block();
+        block.height = None;
+
+        let result = validate_block(
+            &block,
+            &previous_block(),
+            Some(&signer()),
+            &historical_moments(),
+        );
+        assert!(matches!(result, Err(BlockValidationError::MissingHeight)));
+    }
+
+    #[test]
+    fn validate_block_fails_for_wrong_generation_signature() {
+        let mut block = block();
+        block.generation_signature = previous_block().generation_signature;
+
+        let result = validate_block(
+            &block,
+            &previous_block(),
+            Some(&signer()),
+            &historical_moments(),
+        );
+        assert!(matches!(
+            result,
+            Err(BlockValidationError::GenerationSignatureMismatch)
+        ));
+    }
+
+    #[test]
+    fn validate_base_target_checks_difficulty_adjustment_before_poc_plus() {
+        let historical_moments = HistoricalMoments::new().build();
+        let mut block = block();
+        block.height = Some(600_000);
+        block.base_target = 1_000_000;
+        // The last 24 blocks came exactly on time, so the base target stays the same
+        let recent_blocks = (0..24)
+            .map(|i| {
+                let mut recent_block = previous_block();
+                recent_block.base_target = 1_000_000;
+                recent_block.timestamp = block.timestamp - 240 * (24 - i);
+                recent_block
+            })
+            .collect::<Vec<_>>();
+
+        validate_base_target(&block, &recent_blocks, &historical_moments).unwrap();
+        assert!(matches!(
+            validate_base_target(&block, &recent_blocks[1..], &historical_moments),
+            Err(BlockValidationError::UnexpectedError(_))
+        ));
+
+        block.base_target = 1;
+        assert!(matches!(
+            validate_base_target(&block, &recent_blocks, &historical_moments),
+            Err(BlockValidationError::BaseTargetMismatch {
+                expected: 1_000_000,
+                actual: 1
+            })
+        ));
+
+        let poc_plus_enable = historical_moments.poc_plus_enable().into();
+        block.height = Some(poc_plus_enable);
+        assert!(matches!(
+            validate_base_target(&block, &recent_blocks, &historical_moments),
+            Err(BlockValidationError::UnsupportedFork {
+                fork: Fork::PocPlusEnable,
+                height,
+            }) if height == poc_plus_enable
+        ));
+
+        block.height = Some(historical_moments.speedway_enable().into());
+        assert!(matches!(
+            validate_base_target(&block, &recent_blocks, &historical_moments),
+            Err(BlockValidationError::UnsupportedFork {
+                fork: Fork::SpeedwayEnable,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn validate_block_fails_for_deadline_not_elapsed() {
+        // Before the PoC+ fork, the deadline only depends on the hit and the previous block's
+        // base target
+        let mut block = block();
+        block.height = Some(600_000);
+        let mut previous_block = previous_block();
+        previous_block.base_target = u64::MAX;
+        block.previous_block_hash = Some(previous_block.full_hash());
+        block.block_signature = crypto::sign(&block.unsigned_bytes(), PASSPHRASE);
+
+        validate_block(
+            &block,
+            &previous_block,
+            Some(&signer()),
+            &historical_moments(),
+        )
+        .unwrap();
+
+        let mut previous_block = self::previous_block();
+        previous_block.base_target = 1;
+        block.previous_block_hash = Some(previous_block.full_hash());
+        block.block_signature = crypto::sign(&block.unsigned_bytes(), PASSPHRASE);
+        let result = validate_block(
+            &block,
+            &previous_block,
+            Some(&signer()),
+            &historical_moments(),
+        );
+        assert!(matches!(
+            result,
+            Err(BlockValidationError::DeadlineNotElapsed { elapsed: 240, .. })
+        ));
+    }
+
+    #[test]
+    fn validate_block_fails_for_blocks_past_unsupported_forks() {
+        let result = validate_block(
+            &block(),
+            &previous_block(),
+            Some(&signer()),
+            &HistoricalMoments::new().build(),
+        );
+        assert!(matches!(
+            result,
+            Err(BlockValidationError::UnsupportedFork {
+                fork: Fork::SpeedwayEnable,
+                height: 1_200_000
+            })
+        ));
+
+        // The fork is reported before any other rule the block fails
+        let mut block = block();
+        block.previous_block_id = BlockId::from(1);
+        block.total_amount = Amount::from_nqt(0);
+        block.payload_length += 1;
+
+        let result = validate_block(
+            &block,
+            &previous_block(),
+            Some(&signer()),
+            &HistoricalMoments::new().build(),
+        );
+        assert!(matches!(
+            result,
+            Err(BlockValidationError::UnsupportedFork { .. })
+        ));
+    }
+
+    #[test]
+    fn validate_block_checks_signature_against_given_signer() {
+        // A pool miner's blocks are signed by the pool it assigned its rewards to
+        let mut block = block();
+        block.block_signature = crypto::sign(&block.unsigned_bytes(), "pool passphrase");
+        let pool_public_key = crypto::public_key("pool passphrase");
+
+        validate_block(
+            &block,
+            &previous_block(),
+            Some(&pool_public_key),
+            &historical_moments(),
+        )
+        .unwrap();
+        for signer in [Some(&block.generator_public_key), None] {
+            let result = validate_block(&block, &previous_block(), signer, &historical_moments());
+            assert!(matches!(
+                result,
+                Err(BlockValidationError::InvalidBlockSignature)
+            ));
+        }
+    }
+
+    #[test]
+    fn validate_block_fails_for_invalid_signatures() {
+        let mut block = block();
+        block.nonce += 1;
+
+        let result = validate_block(
+            &block,
+            &previous_block(),
+            Some(&signer()),
+            &historical_moments(),
+        );
+        assert!(matches!(
+            result,
+            Err(BlockValidationError::InvalidBlockSignature)
+        ));
+
+        let mut block = self::block();
+        block.transactions[0].signature = [0x11; 64];
+        let payload = block
+            .transactions
+            .iter()
+            .flat_map(Transaction::to_bytes)
+            .collect::<Vec<_>>();
+        block.payload_hash = Sha256::digest(&payload).into();
+        block.block_signature = crypto::sign(&block.unsigned_bytes(), PASSPHRASE);
+
+        let result = validate_block(
+            &block,
+            &previous_block(),
+            Some(&signer()),
+            &historical_moments(),
+        );
+        assert!(matches!(
+            result,
+            Err(BlockValidationError::InvalidTransactionSignature(_))
+        ));
+    }
+
+    #[test]
+    fn validate_block_fails_for_mismatched_payload() {
+        let mut block = block();
+        block.payload_length += 1;
+
+        let result = validate_block(
+            &block,
+            &previous_block(),
+            Some(&signer()),
+            &historical_moments(),
+        );
+        assert!(matches!(
+            result,
+            Err(BlockValidationError::PayloadLengthMismatch { .. })
+        ));
+
+        let mut block = self::block();
+        block.transactions.swap(0, 1);
+
+        let result = validate_block(
+            &block,
+            &previous_block(),
+            Some(&signer()),
+            &historical_moments(),
+        );
+        assert!(matches!(
+            result,
+            Err(BlockValidationError::PayloadHashMismatch)
+        ));
+    }
+
+    #[test]
+    fn validate_block_counts_ats_in_payload_length() {
+        // The fixture's payload is 970 bytes of transactions and 48 bytes of AT state
+        let block = block();
+        assert_eq!(block.payload_length, 1_018);
+
+        validate_block(
+            &block,
+            &previous_block(),
+            Some(&signer()),
+            &historical_moments(),
+        )
+        .unwrap();
+
+        let mut block = self::block();
+        block.block_ats = None;
+        block.block_signature = crypto::sign(&block.unsigned_bytes(), PASSPHRASE);
+
+        let result = validate_block(
+            &block,
+            &previous_block(),
+            Some(&signer()),
+            &historical_moments(),
+        );
+        assert!(matches!(
+            result,
+            Err(BlockValidationError::PayloadLengthMismatch {
+                expected: 1_018,
+                actual: 970
+            })
+        ));
+    }
+
+    /// Returns an ordinary payment that is valid as of now.
+    fn transaction() -> B1Transaction {
+        let mut transaction: B1Transaction = serde_json::from_value(serde_json::json!({
+            "type": 0,
+            "subtype": 0,
+            "timestamp": signum_timestamp(),
+            "deadline": 60,
+            "senderPublicKey": "7347ae2ec9aaa0a93f90a1c394c370a629227928ba2f24843634c81358d2b403",
+            "recipient": "13657951110994294056",
+            "amountNQT": "1000000",
+            "feeNQT": "1000000",
+            "signature": "bc008447457a87c46fcecfe7bcfba4686581d42ba5dae53568983c3411339e08c02222692c561fcc40e8c033ddc0d29fe0308d07570f12facad7f2b3ce895b75",
+            "version": 2,
+            "ecBlockId": "6079409147758822541",
+            "ecBlockHeight": 1237188,
+            "cashBackId": "13420738867631717395"
+        }))
+        .unwrap();
+        transaction.sender_public_key = hex::encode(crypto::public_key(PASSPHRASE));
+        let unsigned_bytes = Transaction::try_from(transaction.clone())
+            .unwrap()
+            .unsigned_bytes();
+        transaction.signature = hex::encode(crypto::sign(&unsigned_bytes, PASSPHRASE));
+        transaction
+    }
+
+    #[test]
+    fn validate_unconfirmed_transaction_succeeds_for_valid_transaction() {
+        validate_unconfirmed_transaction(&transaction(), 0, &HistoricalMoments::new().build())
+            .unwrap();
+    }
+
+    #[test]
+    fn validate_unconfirmed_transaction_fails_for_bad_deadline_or_expiry() {
+        let mut transaction = transaction();
+        transaction.deadline = 0;
+        assert!(matches!(
+            validate_unconfirmed_transaction(&transaction, 0, &HistoricalMoments::new().build()),
+            Err(TransactionValidationError::InvalidDeadline(0))
+        ));
+
+        transaction.deadline = 1;
+        transaction.timestamp -= 60;
+        assert!(matches!(
+            validate_unconfirmed_transaction(&transaction, 0, &HistoricalMoments::new().build()),
+            Err(TransactionValidationError::Expired(_))
+        ));
+    }
+
+    #[test]
+    fn validate_unconfirmed_transaction_fails_for_low_fee() {
+        let mut transaction = transaction();
+        transaction.fee_nqt = 1;
+        assert!(matches!(
+            validate_unconfirmed_transaction(&transaction, 0, &HistoricalMoments::new().build()),
+            Err(TransactionValidationError::FeeTooLow(1))
+        ));
+    }
+
+    #[test]
+    fn validate_unconfirmed_transaction_checks_fee_at_the_later_of_chain_and_ec_block_height() {
+        let historical_moments = HistoricalMoments::new().build();
+        let mut transaction = transaction();
+        transaction.fee_nqt = 735_000;
+        assert!(matches!(
+            validate_unconfirmed_transaction(&transaction, 0, &historical_moments),
+            Err(TransactionValidationError::FeeTooLow(735_000))
+        ));
+
+        // Before the smart fees fork the fee was enough, so the changed signature is what fails
+        transaction.ec_block_height = 600_000;
+        assert!(matches!(
+            validate_unconfirmed_transaction(&transaction, 600_000, &historical_moments),
+            Err(TransactionValidationError::InvalidSignature)
+        ));
+        assert!(matches!(
+            validate_unconfirmed_transaction(&transaction, 1_029_000, &historical_moments),
+            Err(TransactionValidationError::FeeTooLow(735_000))
+        ));
+    }
+
+    #[test]
+    fn validate_unconfirmed_transaction_fails_for_malformed_signature() {
+        let mut transaction = transaction();
+        transaction.signature = "00".repeat(64);
+        assert!(matches!(
+            validate_unconfirmed_transaction(&transaction, 0, &HistoricalMoments::new().build()),
+            Err(TransactionValidationError::MalformedSignature)
+        ));
+    }
+
+    #[test]
+    fn validate_unconfirmed_transaction_fails_for_invalid_signature() {
+        let mut transaction = transaction();
+        transaction.amount_nqt += 1;
+        assert!(matches!(
+            validate_unconfirmed_transaction(&transaction, 0, &HistoricalMoments::new().build()),
+            Err(TransactionValidationError::InvalidSignature)
+        ));
+    }
+}
@@ -1,3 +1,4 @@
 pub mod block_downloader;
 pub mod peer_finder;
 pub mod peer_info_trader;
+pub mod unconfirmed_transaction_puller;
@@ -1,21 +1,28 @@
 use anyhow::Result;
 use num_bigint::BigUint;
-use std::{collections::VecDeque, time::Duration};
+use std::{
+    collections::{HashSet, VecDeque},
+    sync::Arc,
+    time::Duration,
+};
 use tokio::task::{JoinHandle, JoinSet};
 use tracing::{instrument, Instrument};
 use uuid::Uuid;
 
 use crate::{
     configuration::Settings,
-    models::{
-        datastore::Datastore,
-        p2p::{B1Block, PeerAddress},
-    },
-    peers::{B1Peer, BasicPeerClient, DownloadResult},
+    models::{datastore::Datastore, p2p::PeerAddress},
+    peers::{blacklist_peer, B1Peer, BasicPeerClient, DownloadResult},
     statistics_mode,
+    validation::verify_transaction_signatures,
+    Active, HistoricalMoments,
 };
 
-pub async fn run_block_downloader_forever(database: Datastore, settings: Settings) -> Result<()> {
+pub async fn run_block_downloader_forever(
+    database: Datastore,
+    settings: Settings,
+    historical_moments: Arc<HistoricalMoments<Active>>,
+) -> Result<()> {
     loop {
         // Open the job-level span here so we also include the job_id in the error message if this result comes back Error.
         let span = tracing::span!(
@@ -23,9 +30,13 @@ pub async fn run_block_downloader_forever(database: Datastore, settings: Setting
             "Block Downloader",
             job_id = Uuid::new_v4().to_string()
         );
-        let result = block_downloader(database.clone(), settings.clone())
-            .instrument(span)
-            .await;
+        let result = block_downloader(
+            database.clone(),
+            settings.clone(),
+            historical_moments.clone(),
+        )
+        .instrument(span)
+        .await;
         if result.is_err() {
             tracing::error!("Error in block downloader: {:?}", result);
         }
@@ -33,6 +44,13 @@ pub async fn run_block_downloader_forever(database: Datastore, settings: Setting
     }
 }
 
+/// Peers sending more milestones than this in one response are obsolete or malicious.
+const MAX_MILESTONE_BLOCK_IDS: usize = 20;
+
+/// The most `getMilestoneBlockIds` rounds to spend looking for a common block with a peer.
+/// Milestones are at most 1440 blocks apart, so this covers well over two million blocks.
+const MAX_MILESTONE_ROUNDS: usize = 100;
+
 #[derive(Debug)]
 struct DownloadJob {
     start_height: u64,
@@ -43,7 +61,11 @@ struct DownloadJob {
 
 /// This worker queries random peers for new blocks.
 #[tracing::instrument(name = "Block Downloader", skip_all)]
-pub async fn block_downloader(mut database: Datastore, _settings: Settings) -> Result<()> {
+pub async fn block_downloader(
+    mut database: Datastore,
+    _settings: Settings,
+    _historical_moments: Arc<HistoricalMoments<Active>>,
+) -> Result<()> {
     tracing::info!("Would download blocks");
     // Steps:
     // * Initiate FIFO queue
@@ -108,15 +130,29 @@ pub async fn block_downloader(mut database: Datastore, _settings: Settings) -> R
 
     tracing::debug!("Cumulative difficulties: {:?}", cumulative_difficulties);
 
+    let (our_height, our_cumulative_difficulty) = database
+        .get_chain_tip()
+        .await?
+        .map(|(_, height, cumulative_difficulty)| (height, cumulative_difficulty))
+        .unwrap_or_default();
     let highest_cumulative_difficulty = statistics_mode(
         cumulative_difficulties
             .iter()
             .map(|(_peer, cd)| cd)
             .collect::<Vec<_>>(),
     )
-    .unwrap_or(&BigUint::ZERO)
+    .unwrap_or(&our_cumulative_difficulty)
     .to_owned();
 
+    if highest_cumulative_difficulty <= our_cumulative_difficulty {
+        tracing::debug!(
+            "Our chain at height {} has cumulative difficulty {}, which the peers don't exceed",
+            our_height,
+            our_cumulative_difficulty
+        );
+        return Ok(());
+    }
+
     let download_peers = cumulative_difficulties
         .into_iter()
         .filter(|(_peer, cd)| *cd == highest_cumulative_difficulty)
@@ -128,13 +164,27 @@ pub async fn block_downloader(mut database: Datastore, _settings: Settings) -> R
         highest_cumulative_difficulty
     );
 
+    // Start downloading from the highest block we have in common with the network. Peers
+    // return the blocks after the requested height, so this is the common block itself.
+    let start_height = match download_peers.first() {
+        Some(peer) => {
+            let peer = B1Peer::new(peer.clone());
+            match get_common_milestone_block_id(&database, &peer).await? {
+                Some(block_id) => database.get_block_height(block_id).await?.unwrap_or(0),
+                None => 0,
+            }
+        }
+        None => 0,
+    };
+    tracing::debug!("Starting block download at height {}", start_height);
+
     //TODO: Do this in a loop, setting up appropriate sets of blocks
     let mut queued_download_tasks = 1;
     for peer in download_peers {
         tracing::trace!("Queueing {} for block download.", &peer);
         let job = DownloadJob {
             peer,
-            start_height: 10 * queued_download_tasks,
+            start_height: start_height + 10 * (queued_download_tasks - 1),
             number_of_blocks: 10,
             retries: 0,
         };
@@ -177,6 +227,73 @@ pub async fn block_downloader(mut database: Datastore, _settings: Settings) -> R
     Ok(())
 }
 
+/// Finds the highest milestone block that our chain has in common with the peer, following
+/// the `getMilestoneBlockIds` exchange described in `docs/Blockchain Processor Flow.md`.
+///
+/// Returns `None` if we have no chain yet. Peers that send an unreasonable number of
+/// milestones, or only milestones they already sent, are blacklisted. Gives up on the peer
+/// after [`MAX_MILESTONE_ROUNDS`] rounds.
+#[instrument(name = "Find Common Milestone", skip(database))]
+pub async fn get_common_milestone_block_id(
+    database: &Datastore,
+    peer: &B1Peer,
+) -> Result<Option<u64>> {
+    let Some((last_block_id, _, _)) = database.get_chain_tip().await? else {
+        return Ok(None);
+    };
+
+    let mut last_milestone_block_id = None;
+    let mut seen_block_ids = HashSet::new();
+    for _ in 0..MAX_MILESTONE_ROUNDS {
+        let milestones = match last_milestone_block_id {
+            None => peer.get_milestone_block_ids(Some(last_block_id), None),
+            Some(milestone) => peer.get_milestone_block_ids(None, Some(milestone)),
+        }
+        .await?;
+
+        if milestones.block_ids.is_empty() {
+            // The peer has nothing but the genesis block in common with us
+            return Ok(database.get_block_id_at_height(0).await?);
+        }
+
+        if milestones.block_ids.len() > MAX_MILESTONE_BLOCK_IDS {
+            blacklist_peer(database.clone(), peer.address()).await?;
+            anyhow::bail!(
+                "peer {} sent too many milestone block ids ({})",
+                peer.address(),
+                milestones.block_ids.len()
+            );
+        }
+
+        // A peer walking down its chain sends new milestones every round
+        let new_block_ids = milestones
+            .block_ids
+            .iter()
+            .filter(|block_id| seen_block_ids.insert(**block_id))
+            .count();
+        if new_block_ids == 0 {
+            blacklist_peer(database.clone(), peer.address()).await?;
+            anyhow::bail!(
+                "peer {} sent the same milestone block ids again",
+                peer.address()
+            );
+        }
+
+        for block_id in milestones.block_ids {
+            if database.get_block_height(block_id).await?.is_some() {
+                return Ok(Some(block_id));
+            }
+            last_milestone_block_id = Some(block_id);
+        }
+    }
+
+    anyhow::bail!(
+        "no common block found with peer {} after {} rounds of milestones",
+        peer.address(),
+        MAX_MILESTONE_ROUNDS
+    )
+}
+
 //TODO: Rework the output of this to use a custom error type that includes the reason and DownloadJob
 #[instrument(name = "Download Blocks Task")]
 async fn download_blocks_task(job: DownloadJob) -> Result<DownloadResult, DownloadJob> {
@@ -204,13 +321,20 @@ async fn download_blocks_task(job: DownloadJob) -> Result<DownloadResult, Downlo
         }
     };
 
+    // Who signs a block depends on the chain before it, so the block signatures are checked
+    // once the blocks are validated against it
+    if let Some(e) = result
+        .blocks
+        .iter()
+        .find_map(|block| verify_transaction_signatures(block).err())
+    {
+        tracing::warn!("{} sent a block with a bad signature: {}", &job.peer, e);
+        return Err(job);
+    }
+
     //TODO: Process the blocks just downloaded and return the correct Result
     // - OK if all in this subchain are good
     // - Connection error for any connectivity issues
     // - Parse or Verification error for bad blocks
     Ok(result)
 }
-
-async fn _verify_b1_block(_block: B1Block) -> anyhow::Result<B1Block> {
-    todo!()
-}
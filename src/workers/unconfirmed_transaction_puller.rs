@@ -0,0 +1,90 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::{Context, Result};
+use tracing::Instrument;
+use uuid::Uuid;
+
+use crate::{
+    models::datastore::Datastore,
+    peers::{B1Peer, BasicPeerClient},
+    signum_timestamp,
+    validation::validate_unconfirmed_transaction,
+    Active, HistoricalMoments,
+};
+
+#[tracing::instrument(skip_all)]
+pub async fn run_unconfirmed_transaction_puller_forever(
+    database: Datastore,
+    historical_moments: Arc<HistoricalMoments<Active>>,
+) -> Result<()> {
+    tracing::info!("Starting unconfirmed transaction puller task");
+    loop {
+        // Open the job-level span here so we also include the job_id in the error message if this result comes back Error.
+        let span = tracing::span!(
+            tracing::Level::INFO,
+            "Unconfirmed Transaction Pull Task",
+            job_id = Uuid::new_v4().to_string()
+        );
+        let result = unconfirmed_transaction_puller(database.clone(), &historical_moments)
+            .instrument(span)
+            .await;
+        if result.is_err() {
+            tracing::error!("Error in unconfirmed transaction puller: {:?}", result);
+        }
+        tokio::time::sleep(Duration::from_secs(10)).await;
+    }
+}
+
+/// Removes expired transactions from the unconfirmed transaction pool, then pulls the
+/// unconfirmed transactions of a random peer into it.
+///
+/// A peer that sends an invalid transaction is blacklisted.
+#[tracing::instrument(name = "Unconfirmed Transaction Puller", skip_all)]
+pub async fn unconfirmed_transaction_puller(
+    mut database: Datastore,
+    historical_moments: &HistoricalMoments<Active>,
+) -> Result<()> {
+    database
+        .remove_expired_unconfirmed_transactions(signum_timestamp())
+        .await?;
+
+    let peer_address = database.get_random_peer().await?;
+    let peer = B1Peer::new(peer_address.clone());
+
+    let transactions = peer.get_unconfirmed_transactions().await.context(format!(
+        "unable to get unconfirmed transactions from {}",
+        &peer_address
+    ))?;
+
+    let next_height = database
+        .get_chain_tip()
+        .await?
+        .map_or(0, |(_, height, _)| height + 1);
+
+    let mut new_transactions = 0;
+    for transaction in transactions {
+        if let Err(e) =
+            validate_unconfirmed_transaction(&transaction, next_height, historical_moments)
+        {
+            tracing::warn!(
+                "Blacklisting {} for sending an invalid transaction: {}",
+                &peer_address,
+                e
+            );
+            database.blacklist_peer(&peer_address).await?;
+            break;
+        }
+
+        if database.add_unconfirmed_transaction(&transaction).await? {
+            new_transactions += 1;
+        }
+    }
+
+    tracing::info!(
+        "Pulled {} new unconfirmed transactions from {}",
+        new_transactions,
+        &peer_address
+    );
+
+    Ok(())
+}
@@ -0,0 +1,864 @@
+use std::str::FromStr;
+
+use anyhow::Context;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use signum_node_rs::{
+    crypto,
+    genesis::{self, initialize_chain, ChainInitializationError},
+    models::{
+        datastore::DatastoreError,
+        p2p::{B1Block, B1Transaction, PeerAddress, PeerInfo, Transaction},
+        AccountId, Block,
+    },
+    network::Network,
+    peers::{B1Peer, BasicPeerClient},
+    poc,
+};
+
+use crate::helpers::{
+    fixture_block, sign_block, sign_transaction, spawn_app, TestApp, POC_PLUS_ENABLE,
+};
+
+#[tokio::test]
+async fn srs_api_handler_returns_valid_data_for_get_cumulative_difficulty_request(
+) -> Result<(), anyhow::Error> {
+    // Arrange
+    let app = spawn_app().await;
+
+    let client = reqwest::Client::new();
+
+    let body = serde_json::json!({
+        "protocol": "B1",
+        "requestType": "getCumulativeDifficulty",
+    });
+
+    // Act
+    let response = client
+        .post(format!("{}/", &app.address))
+        .header("Content-Type", "application/json")
+        .json(&body)
+        .send()
+        .await
+        .expect("failed to execute request");
+
+    // Assert
+    assert!(response.status().is_success());
+    let json = response
+        .json::<serde_json::Value>()
+        .await
+        .context("couldn't deserialize json")?;
+
+    assert_eq!(json["cumulativeDifficulty"], "0");
+    assert_eq!(json["blockchainHeight"], 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn get_milestone_block_ids_returns_last_block_id_when_it_is_our_tip() {
+    // Arrange
+    let app = spawn_app().await;
+    app.insert_test_chain(100).await;
+
+    // Act
+    let json = app
+        .post_p2p_request(&json!({
+            "requestType": "getMilestoneBlockIds",
+            "lastBlockId": "1099",
+        }))
+        .await;
+
+    // Assert
+    assert_eq!(json, json!({ "milestoneBlockIds": ["1099"], "last": true }));
+}
+
+#[tokio::test]
+async fn get_milestone_block_ids_returns_known_last_block_id_without_last_flag() {
+    // Arrange
+    let app = spawn_app().await;
+    app.insert_test_chain(100).await;
+
+    // Act
+    let json = app
+        .post_p2p_request(&json!({
+            "requestType": "getMilestoneBlockIds",
+            "lastBlockId": 1050,
+        }))
+        .await;
+
+    // Assert
+    assert_eq!(json, json!({ "milestoneBlockIds": ["1050"] }));
+}
+
+#[tokio::test]
+async fn get_milestone_block_ids_steps_down_from_tip_for_unknown_last_block_id() {
+    // Arrange
+    let app = spawn_app().await;
+    app.insert_test_chain(100).await;
+
+    // Act
+    let json = app
+        .post_p2p_request(&json!({
+            "requestType": "getMilestoneBlockIds",
+            "lastBlockId": "123456789",
+        }))
+        .await;
+
+    // Assert
+    let expected = (0..10)
+        .map(|i| (1099 - i * 10).to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(json, json!({ "milestoneBlockIds": expected }));
+}
+
+#[tokio::test]
+async fn get_milestone_block_ids_continues_from_last_milestone() {
+    // Arrange
+    let app = spawn_app().await;
+    app.insert_test_chain(2000).await;
+
+    // Act
+    let json = app
+        .post_p2p_request(&json!({
+            "requestType": "getMilestoneBlockIds",
+            "lastMilestoneBlockId": "2899",
+        }))
+        .await;
+
+    // Assert
+    // Milestone is at height 1899 and the tip at 1999, so the jump is 100.
+    let expected = (0..10)
+        .map(|i| (2799 - i * 100).to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(json, json!({ "milestoneBlockIds": expected }));
+}
+
+#[tokio::test]
+async fn b1_peer_gets_milestone_block_ids_from_peer() -> Result<(), anyhow::Error> {
+    // Arrange
+    let app = spawn_app().await;
+    app.insert_test_chain(30).await;
+    let peer = B1Peer::new(PeerAddress::from_str(&format!("localhost:{}", app.port))?);
+
+    // Act
+    let at_tip = peer.get_milestone_block_ids(Some(1029), None).await?;
+    let from_milestone = peer.get_milestone_block_ids(None, Some(1025)).await?;
+
+    // Assert
+    assert_eq!(at_tip.block_ids, vec![1029]);
+    assert!(at_tip.last);
+    assert_eq!(
+        from_milestone.block_ids,
+        vec![1021, 1017, 1013, 1009, 1005, 1001]
+    );
+    assert!(!from_milestone.last);
+    Ok(())
+}
+
+#[tokio::test]
+async fn get_next_block_ids_returns_following_block_ids() {
+    // Arrange
+    let app = spawn_app().await;
+    app.insert_test_chain(10).await;
+
+    // Act
+    let known = app
+        .post_p2p_request(&json!({
+            "requestType": "getNextBlockIds",
+            "blockId": "1005",
+        }))
+        .await;
+    let unknown = app
+        .post_p2p_request(&json!({
+            "requestType": "getNextBlockIds",
+            "blockId": "17655301179078078080",
+        }))
+        .await;
+
+    // Assert
+    assert_eq!(
+        known,
+        json!({ "nextBlockIds": ["1006", "1007", "1008", "1009"] })
+    );
+    assert_eq!(unknown, json!({ "nextBlockIds": [] }));
+}
+
+#[tokio::test]
+async fn get_next_blocks_returns_following_blocks() {
+    // Arrange
+    let app = spawn_app().await;
+    app.insert_test_chain(10).await;
+
+    // Act
+    let json = app
+        .post_p2p_request(&json!({
+            "requestType": "getNextBlocks",
+            "blockId": "1007",
+        }))
+        .await;
+
+    // Assert
+    let next_blocks = json["nextBlocks"].as_array().unwrap();
+    assert_eq!(next_blocks.len(), 2);
+    assert_eq!(next_blocks[0]["previousBlock"], "1007");
+    assert_eq!(next_blocks[1]["previousBlock"], "1008");
+    assert_eq!(
+        next_blocks[0]["blockSignature"],
+        fixture_block()["blockSignature"]
+    );
+}
+
+#[tokio::test]
+async fn b1_peer_gets_next_block_ids_and_blocks_from_peer() -> Result<(), anyhow::Error> {
+    // Arrange
+    let app = spawn_app().await;
+    app.insert_test_chain(5).await;
+    let peer = B1Peer::new(PeerAddress::from_str(&format!("localhost:{}", app.port))?);
+
+    // Act
+    let next_block_ids = peer.get_next_block_ids(1001).await?;
+    let next_blocks = peer.get_next_blocks(1002).await?;
+
+    // Assert
+    assert_eq!(next_block_ids, vec![1002, 1003, 1004]);
+    assert_eq!(
+        next_blocks
+            .iter()
+            .map(|b| b.previous_block)
+            .collect::<Vec<_>>(),
+        vec![1002, 1003]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn get_blocks_from_height_returns_blocks_after_height() {
+    // Arrange
+    let app = spawn_app().await;
+    app.insert_test_chain(10).await;
+
+    // Act
+    let json = app
+        .post_p2p_request(&json!({
+            "protocol": "B1",
+            "requestType": "getBlocksFromHeight",
+            "height": 3,
+            "numBlocks": 2,
+        }))
+        .await;
+
+    // Assert
+    let next_blocks = json["nextBlocks"].as_array().unwrap();
+    assert_eq!(next_blocks.len(), 2);
+    assert_eq!(next_blocks[0]["previousBlock"], "1003");
+    assert_eq!(next_blocks[1]["previousBlock"], "1004");
+
+    let mut expected = fixture_block();
+    expected["previousBlock"] = json!("1003");
+    expected["timestamp"] = json!(4);
+    assert_eq!(next_blocks[0], expected);
+}
+
+#[tokio::test]
+async fn get_blocks_from_height_falls_back_to_default_batch_for_invalid_num_blocks() {
+    // Arrange
+    let app = spawn_app().await;
+    app.insert_test_chain(150).await;
+
+    // Act
+    let json = app
+        .post_p2p_request(&json!({
+            "requestType": "getBlocksFromHeight",
+            "height": 0,
+            "numBlocks": 5000,
+        }))
+        .await;
+
+    // Assert
+    assert_eq!(json["nextBlocks"].as_array().unwrap().len(), 100);
+}
+
+/// Returns the fixture block's transactions signed by the test account, so they differ from
+/// the ones confirmed with the fixture block.
+fn unconfirmed_transactions() -> Vec<serde_json::Value> {
+    let mut transactions = fixture_block()["transactions"].as_array().unwrap().clone();
+    transactions.iter_mut().for_each(sign_transaction);
+    transactions
+}
+
+/// Returns the fixture block as a `processBlock` request on top of the test chain tip, carrying
+/// the given transactions and the base target of the chain stored by [`store_fixture_chain`].
+fn process_block_request(
+    previous_block: u64,
+    previous_block_hash: &str,
+    transactions: Vec<serde_json::Value>,
+) -> serde_json::Value {
+    let mut block = fixture_block();
+    block["transactions"] = json!(transactions);
+    block["previousBlock"] = json!(previous_block.to_string());
+    block["previousBlockHash"] = json!(previous_block_hash);
+    // Late enough for any hit to have reached its deadline at the chain's base target
+    block["timestamp"] = json!(block["timestamp"].as_u64().unwrap() + 1_200);
+    block["baseTarget"] = json!(FIXTURE_CHAIN_BASE_TARGET.to_string());
+    let fixture =
+        Block::try_from(serde_json::from_value::<B1Block>(fixture_block()).unwrap()).unwrap();
+    block["generationSignature"] =
+        json!(hex::encode(poc::calculate_generation_signature(&fixture)));
+    // The block no longer carries the fixture's transactions, so fix up the totals and payload
+    let transactions = block["transactions"].as_array().unwrap();
+    let total_amount: u64 = transactions
+        .iter()
+        .map(|t| t["amountNQT"].as_u64().unwrap())
+        .sum();
+    let total_fee: u64 = transactions
+        .iter()
+        .map(|t| t["feeNQT"].as_u64().unwrap())
+        .sum();
+    let payload = transactions
+        .iter()
+        .map(|t| serde_json::from_value::<B1Transaction>(t.clone()).unwrap())
+        .flat_map(|t| Transaction::try_from(t).unwrap().to_bytes())
+        .collect::<Vec<_>>();
+    block["totalAmountNQT"] = json!(total_amount);
+    block["totalFeeNQT"] = json!(total_fee);
+    // The AT state the block carries counts towards its payload length
+    let block_ats_length = block["blockATs"].as_str().map_or(0, |ats| ats.len() / 2);
+    block["payloadLength"] = json!(payload.len() + block_ats_length);
+    block["payloadHash"] = json!(hex::encode(Sha256::digest(&payload)));
+    sign_block(&mut block);
+    block["protocol"] = json!("B1");
+    block["requestType"] = json!("processBlock");
+    block
+}
+
+/// The height the fixture block is stored at by [`store_fixture_tip`]. The fixture is a mainnet
+/// block from after the smart token fork, so its transactions are only valid from there on.
+const FIXTURE_TIP_HEIGHT: u64 = 1_200_000;
+
+/// The cumulative difficulty the fixture block is stored with by [`store_fixture_tip`].
+const FIXTURE_TIP_CUMULATIVE_DIFFICULTY: u64 = 1_000_000;
+
+/// The base target of the blocks stored by [`store_fixture_chain`]. At the maximum, deadlines
+/// are the shortest they can be, and the late blocks [`process_block_request`] pushes keep the
+/// base target there.
+const FIXTURE_CHAIN_BASE_TARGET: u64 = poc::MAX_BASE_TARGET;
+
+/// Stores the fixture block as the only block of the chain, returning its ID and full hash.
+async fn store_fixture_tip(app: &TestApp) -> (u64, String) {
+    let block = serde_json::from_value::<B1Block>(fixture_block()).unwrap();
+    store_tip(app, &block, FIXTURE_TIP_HEIGHT).await
+}
+
+/// Stores the fixture block as the tip of a chain long enough to check the next block's base
+/// target against, returning the tip's ID and full hash.
+///
+/// The blocks before the tip are copies of it without transactions, forged one block time
+/// apart.
+async fn store_fixture_chain(app: &TestApp) -> (u64, String) {
+    let mut tip = serde_json::from_value::<B1Block>(fixture_block()).unwrap();
+    tip.base_target = FIXTURE_CHAIN_BASE_TARGET;
+    for blocks_before_tip in (1..24).rev() {
+        let mut block = tip.clone();
+        block.transactions = Vec::new();
+        block.timestamp -= 240 * blocks_before_tip;
+        let block_id = Block::try_from(block.clone()).unwrap().id;
+        app.datastore
+            .store_block(
+                &block,
+                block_id.into(),
+                FIXTURE_TIP_HEIGHT - blocks_before_tip,
+                &0u8.into(),
+            )
+            .await
+            .expect("failed to store a block before the fixture block");
+    }
+    store_tip(app, &tip, FIXTURE_TIP_HEIGHT).await
+}
+
+/// Stores `block` at `height` with [`FIXTURE_TIP_CUMULATIVE_DIFFICULTY`], returning its ID and
+/// full hash.
+async fn store_tip(app: &TestApp, block: &B1Block, height: u64) -> (u64, String) {
+    let domain_block = Block::try_from(block.clone()).unwrap();
+    app.datastore
+        .store_block(
+            block,
+            domain_block.id.into(),
+            height,
+            &FIXTURE_TIP_CUMULATIVE_DIFFICULTY.into(),
+        )
+        .await
+        .expect("failed to store the fixture block");
+    (
+        domain_block.id.into(),
+        hex::encode(domain_block.full_hash()),
+    )
+}
+
+#[tokio::test]
+async fn process_block_accepts_block_extending_tip() {
+    // Arrange
+    let app = spawn_app().await;
+    let (tip_id, tip_hash) = store_fixture_chain(&app).await;
+
+    // Act
+    let json = app
+        .post_p2p_request(&process_block_request(
+            tip_id,
+            &tip_hash,
+            unconfirmed_transactions(),
+        ))
+        .await;
+
+    // Assert
+    assert_eq!(json, json!({ "accepted": true }));
+    let cumulative_difficulty = app
+        .post_p2p_request(&json!({ "requestType": "getCumulativeDifficulty" }))
+        .await;
+    assert_eq!(
+        cumulative_difficulty["blockchainHeight"],
+        FIXTURE_TIP_HEIGHT + 1
+    );
+    assert_eq!(
+        cumulative_difficulty["cumulativeDifficulty"],
+        poc::calculate_cumulative_difficulty(
+            &FIXTURE_TIP_CUMULATIVE_DIFFICULTY.into(),
+            FIXTURE_CHAIN_BASE_TARGET
+        )
+        .to_string()
+    );
+}
+
+#[tokio::test]
+async fn process_block_ignores_block_not_extending_tip() {
+    // Arrange
+    let app = spawn_app().await;
+    app.insert_test_chain(5).await;
+
+    // Act
+    let json = app
+        .post_p2p_request(&process_block_request(
+            1003,
+            &"00".repeat(32),
+            unconfirmed_transactions(),
+        ))
+        .await;
+
+    // Assert
+    assert_eq!(json, json!({ "accepted": false }));
+    let cumulative_difficulty = app
+        .post_p2p_request(&json!({ "requestType": "getCumulativeDifficulty" }))
+        .await;
+    assert_eq!(cumulative_difficulty["blockchainHeight"], 4);
+}
+
+#[tokio::test]
+async fn process_block_rejects_invalid_block_and_blacklists_sender() -> Result<(), anyhow::Error> {
+    // Arrange
+    let mut app = spawn_app().await;
+    let (tip_id, tip_hash) = store_fixture_tip(&app).await;
+
+    let peer = PeerAddress::from_str("sender.example.com")?;
+    app.datastore.create_new_peer(&peer).await?;
+    app.datastore
+        .update_peer_info(
+            peer.clone(),
+            "127.0.0.1".to_string(),
+            PeerInfo {
+                announced_address: Some(peer.clone()),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+    let mut body = process_block_request(tip_id, &tip_hash, unconfirmed_transactions());
+    body["totalAmountNQT"] = json!(1);
+
+    // Act
+    let json = app.post_p2p_request(&body).await;
+
+    // Assert
+    assert_eq!(json, json!({ "accepted": false }));
+    let mut blacklist = app
+        .datastore
+        .get_surreal_db()
+        .query(
+            "SELECT VALUE blacklist.count FROM ONLY peer WHERE announced_address = $peer LIMIT 1",
+        )
+        .bind(("peer", peer))
+        .await?;
+    assert_eq!(blacklist.take::<Option<u64>>(0)?, Some(1));
+    Ok(())
+}
+
+#[tokio::test]
+async fn process_block_ignores_block_past_unsupported_fork_without_blacklisting_sender(
+) -> Result<(), anyhow::Error> {
+    // Arrange
+    let mut app = spawn_app().await;
+    let tip = serde_json::from_value::<B1Block>(fixture_block())?;
+    let (tip_id, tip_hash) = store_tip(&app, &tip, POC_PLUS_ENABLE.into()).await;
+
+    let peer = PeerAddress::from_str("sender.example.com")?;
+    app.datastore.create_new_peer(&peer).await?;
+    app.datastore
+        .update_peer_info(
+            peer.clone(),
+            "127.0.0.1".to_string(),
+            PeerInfo {
+                announced_address: Some(peer.clone()),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+    // The fork is checked first, so a block failing the rules before it isn't held against it
+    let mut body = process_block_request(tip_id, &tip_hash, unconfirmed_transactions());
+    body["totalAmountNQT"] = json!(1);
+
+    // Act
+    let json = app.post_p2p_request(&body).await;
+
+    // Assert
+    assert_eq!(json, json!({ "accepted": false }));
+    let mut blacklist = app
+        .datastore
+        .get_surreal_db()
+        .query(
+            "SELECT VALUE blacklist.count FROM ONLY peer WHERE announced_address = $peer LIMIT 1",
+        )
+        .bind(("peer", peer))
+        .await?;
+    assert_eq!(blacklist.take::<Option<u64>>(0)?.unwrap_or_default(), 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn process_block_fails_without_blacklisting_sender_when_our_chain_is_too_short(
+) -> Result<(), anyhow::Error> {
+    // Arrange
+    let mut app = spawn_app().await;
+    let (tip_id, tip_hash) = store_fixture_tip(&app).await;
+
+    let peer = PeerAddress::from_str("sender.example.com")?;
+    app.datastore.create_new_peer(&peer).await?;
+    app.datastore
+        .update_peer_info(
+            peer.clone(),
+            "127.0.0.1".to_string(),
+            PeerInfo {
+                announced_address: Some(peer.clone()),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+    // Act
+    let response = reqwest::Client::new()
+        .post(format!("{}/", &app.address))
+        .json(&process_block_request(
+            tip_id,
+            &tip_hash,
+            unconfirmed_transactions(),
+        ))
+        .send()
+        .await?;
+
+    // Assert
+    assert_eq!(
+        response.status(),
+        reqwest::StatusCode::INTERNAL_SERVER_ERROR
+    );
+    let mut blacklist = app
+        .datastore
+        .get_surreal_db()
+        .query(
+            "SELECT VALUE blacklist.count FROM ONLY peer WHERE announced_address = $peer LIMIT 1",
+        )
+        .bind(("peer", peer))
+        .await?;
+    assert_eq!(blacklist.take::<Option<u64>>(0)?.unwrap_or_default(), 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn process_block_rejects_block_with_already_confirmed_transactions() {
+    // Arrange
+    let app = spawn_app().await;
+    let (tip_id, tip_hash) = store_fixture_chain(&app).await;
+    let confirmed_transactions = fixture_block()["transactions"].as_array().unwrap().clone();
+
+    // Act
+    let json = app
+        .post_p2p_request(&process_block_request(
+            tip_id,
+            &tip_hash,
+            confirmed_transactions,
+        ))
+        .await;
+
+    // Assert
+    assert_eq!(json, json!({ "accepted": false }));
+    let cumulative_difficulty = app
+        .post_p2p_request(&json!({ "requestType": "getCumulativeDifficulty" }))
+        .await;
+    assert_eq!(
+        cumulative_difficulty["blockchainHeight"],
+        FIXTURE_TIP_HEIGHT
+    );
+}
+
+#[tokio::test]
+async fn b1_peer_gets_blocks_from_height_as_domain_blocks() -> Result<(), anyhow::Error> {
+    // Arrange
+    let app = spawn_app().await;
+    app.insert_test_chain(5).await;
+    let peer = B1Peer::new(PeerAddress::from_str(&format!("localhost:{}", app.port))?);
+
+    // Act
+    let result = peer.get_blocks_from_height(1, 10).await?;
+
+    // Assert
+    let heights = result.blocks.iter().map(|b| b.height).collect::<Vec<_>>();
+    assert_eq!(heights, vec![Some(2), Some(3), Some(4)]);
+    assert_eq!(u64::from(result.blocks[0].previous_block_id), 1001);
+    Ok(())
+}
+
+#[tokio::test]
+async fn initialize_chain_stores_genesis_block_once_and_rejects_other_networks(
+) -> Result<(), anyhow::Error> {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    initialize_chain(&app.datastore, Network::Mainnet).await?;
+    initialize_chain(&app.datastore, Network::Mainnet).await?;
+    let result = initialize_chain(&app.datastore, Network::Devnet).await;
+
+    // Assert
+    assert_eq!(
+        app.datastore
+            .get_chain_tip()
+            .await?
+            .map(|(block_id, height, _)| (block_id, height)),
+        Some((genesis::SIGNUM_GENESIS_BLOCK_ID, 0))
+    );
+    assert!(matches!(
+        result,
+        Err(ChainInitializationError::GenesisBlockMismatch { .. })
+    ));
+    Ok(())
+}
+
+#[tokio::test]
+async fn stored_block_can_be_found_by_height_generator_and_as_tip() -> Result<(), anyhow::Error> {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let (block_id, _) = store_fixture_tip(&app).await;
+
+    // Assert
+    let block = Block::try_from(
+        app.datastore
+            .get_block_at_height(FIXTURE_TIP_HEIGHT)
+            .await?
+            .unwrap(),
+    )?;
+    assert_eq!(u64::from(block.id), block_id);
+    let (tip_id, height, _) = app.datastore.get_chain_tip().await?.unwrap();
+    assert_eq!(tip_id, block_id);
+    assert_eq!(height, FIXTURE_TIP_HEIGHT);
+    let generator = AccountId::from_public_key(&block.generator_public_key);
+    assert_eq!(
+        app.datastore
+            .get_blocks_by_generator(generator, 10)
+            .await?
+            .len(),
+        1
+    );
+    assert!(app
+        .datastore
+        .get_blocks_by_generator(AccountId::from(1), 10)
+        .await?
+        .is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn stored_block_transactions_can_be_found_by_id_full_hash_and_account(
+) -> Result<(), anyhow::Error> {
+    // Arrange
+    let app = spawn_app().await;
+    let fixture = serde_json::from_value::<B1Block>(fixture_block())?;
+    let transaction = Transaction::try_from(fixture.transactions[0].clone())?;
+
+    // Act
+    store_fixture_tip(&app).await;
+
+    // Assert
+    let found = app
+        .datastore
+        .get_transaction(transaction.id.into())
+        .await?
+        .unwrap();
+    assert_eq!(found.signature, fixture.transactions[0].signature);
+    let found = app
+        .datastore
+        .get_transaction_by_full_hash(&transaction.full_hash)
+        .await?
+        .unwrap();
+    assert_eq!(found.signature, fixture.transactions[0].signature);
+    let sender = AccountId::from_public_key(&transaction.sender_public_key);
+    let sent = app
+        .datastore
+        .get_transactions_by_account(sender, 100)
+        .await?;
+    assert!(sent
+        .iter()
+        .any(|t| t.signature == fixture.transactions[0].signature));
+    assert!(app.datastore.get_transaction(1).await?.is_none());
+    assert_eq!(
+        app.datastore
+            .get_confirmed_transaction_ids(&[1, transaction.id.into()])
+            .await?,
+        vec![u64::from(transaction.id)]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn store_block_stores_nothing_if_a_transaction_cannot_be_stored() -> Result<(), anyhow::Error>
+{
+    // Arrange
+    let app = spawn_app().await;
+    store_fixture_tip(&app).await;
+    let block = serde_json::from_value::<B1Block>(fixture_block())?;
+
+    // Act
+    // The same transactions can't be confirmed twice
+    let result = app
+        .datastore
+        .store_block(&block, 1, FIXTURE_TIP_HEIGHT + 1, &0u8.into())
+        .await;
+
+    // Assert
+    assert!(result.is_err());
+    assert!(app.datastore.get_block(1).await?.is_none());
+    assert_eq!(
+        app.datastore.get_chain_tip().await?.map(|(_, h, _)| h),
+        Some(FIXTURE_TIP_HEIGHT)
+    );
+    Ok(())
+}
+
+/// Returns an unsigned transaction of the given type from the account with the public key to
+/// `recipient`. The timestamp tells transactions of the same accounts apart.
+fn transaction_between(
+    (transaction_type, subtype): (u8, u8),
+    sender_public_key: &[u8; 32],
+    recipient: AccountId,
+    timestamp: u64,
+) -> serde_json::Value {
+    let mut transaction = fixture_block()["transactions"][0].clone();
+    transaction["type"] = json!(transaction_type);
+    transaction["subtype"] = json!(subtype);
+    transaction["timestamp"] = json!(timestamp);
+    transaction["senderPublicKey"] = json!(hex::encode(sender_public_key));
+    transaction["recipient"] = json!(recipient.to_string());
+    transaction
+}
+
+/// Stores a block carrying the given transactions at `height`, without validating it.
+async fn store_block_with_transactions(
+    app: &TestApp,
+    height: u64,
+    transactions: Vec<serde_json::Value>,
+) {
+    let mut block = fixture_block();
+    block["transactions"] = json!(transactions);
+    let block = serde_json::from_value::<B1Block>(block).unwrap();
+    app.datastore
+        .store_block(&block, 1000 + height, height, &0u8.into())
+        .await
+        .expect("failed to store block");
+}
+
+#[tokio::test]
+async fn block_signer_is_the_reward_recipient_once_its_assignment_takes_effect(
+) -> Result<(), anyhow::Error> {
+    // Arrange
+    let app = spawn_app().await;
+    let miner = crypto::public_key("miner passphrase");
+    let pool = crypto::public_key("pool passphrase");
+    let new_pool = crypto::public_key("new pool passphrase");
+    let assign_to = |recipient: &[u8; 32], timestamp| {
+        transaction_between(
+            (20, 0),
+            &miner,
+            AccountId::from_public_key(recipient),
+            timestamp,
+        )
+    };
+
+    // The pool's public key is known from a payment it sent, the new pool's isn't
+    store_block_with_transactions(
+        &app,
+        10,
+        vec![
+            assign_to(&pool, 1),
+            transaction_between((0, 0), &pool, AccountId::from(1), 2),
+        ],
+    )
+    .await;
+    store_block_with_transactions(&app, 20, vec![assign_to(&new_pool, 3)]).await;
+
+    // Act
+    let mut signers = Vec::new();
+    for height in [10, 14, 15, 24, 25] {
+        signers.push(
+            app.datastore
+                .get_block_signer_public_key(&miner, height)
+                .await?,
+        );
+    }
+    let solo_signer = app.datastore.get_block_signer_public_key(&pool, 25).await?;
+
+    // Assert
+    assert_eq!(
+        signers,
+        vec![Some(miner), Some(miner), Some(pool), Some(pool), None]
+    );
+    assert_eq!(solo_signer, Some(pool));
+    Ok(())
+}
+
+#[tokio::test]
+async fn store_block_reports_height_already_taken() -> Result<(), anyhow::Error> {
+    // Arrange
+    let app = spawn_app().await;
+    let (tip_id, _) = store_fixture_tip(&app).await;
+    let mut block = serde_json::from_value::<B1Block>(fixture_block())?;
+    block.transactions = Vec::new();
+
+    // Act
+    let result = app
+        .datastore
+        .store_block(&block, 1, FIXTURE_TIP_HEIGHT, &0u8.into())
+        .await;
+
+    // Assert
+    assert!(matches!(
+        result,
+        Err(DatastoreError::HeightTaken(FIXTURE_TIP_HEIGHT))
+    ));
+    assert_eq!(
+        app.datastore.get_chain_tip().await?,
+        Some((
+            tip_id,
+            FIXTURE_TIP_HEIGHT,
+            FIXTURE_TIP_CUMULATIVE_DIFFICULTY.into()
+        ))
+    );
+    Ok(())
+}
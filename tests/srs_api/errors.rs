@@ -0,0 +1,117 @@
+use reqwest::StatusCode;
+use serde_json::json;
+
+use crate::helpers::spawn_app;
+
+/// Posts a raw body to the P2P API and returns the status and parsed JSON response.
+async fn post_raw(address: &str, body: &str) -> (StatusCode, serde_json::Value) {
+    let response = reqwest::Client::new()
+        .post(format!("{}/", address))
+        .header("Content-Type", "application/json")
+        .body(body.to_string())
+        .send()
+        .await
+        .expect("failed to execute request");
+    let status = response.status();
+    let json = response
+        .json::<serde_json::Value>()
+        .await
+        .expect("couldn't deserialize json");
+    (status, json)
+}
+
+#[tokio::test]
+async fn srs_api_handler_rejects_unknown_request_type() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let (status, json) = post_raw(
+        &app.address,
+        r#"{"protocol": "B1", "requestType": "getAccount"}"#,
+    )
+    .await;
+
+    // Assert
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json, json!({ "error": "Unsupported request type!" }));
+}
+
+#[tokio::test]
+async fn srs_api_handler_rejects_request_type_that_is_not_a_string() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let (status, json) = post_raw(&app.address, r#"{"protocol": "B1", "requestType": 1}"#).await;
+
+    // Assert
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json, json!({ "error": "Unsupported request type!" }));
+}
+
+#[tokio::test]
+async fn srs_api_handler_rejects_missing_request_type() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let (status, json) = post_raw(&app.address, r#"{"protocol": "B1"}"#).await;
+
+    // Assert
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json, json!({ "error": "Unsupported request type!" }));
+}
+
+#[tokio::test]
+async fn srs_api_handler_rejects_wrong_protocol() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let (status, json) = post_raw(
+        &app.address,
+        r#"{"protocol": "B2", "requestType": "getPeers"}"#,
+    )
+    .await;
+
+    // Assert
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json, json!({ "error": "Unsupported protocol!" }));
+}
+
+#[tokio::test]
+async fn srs_api_handler_rejects_invalid_payload() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let (status, json) = post_raw(
+        &app.address,
+        r#"{"protocol": "B1", "requestType": "getNextBlockIds", "blockId": "not a number"}"#,
+    )
+    .await;
+
+    // Assert
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(json["error"]
+        .as_str()
+        .unwrap()
+        .starts_with("Invalid request payload: "));
+}
+
+#[tokio::test]
+async fn srs_api_handler_rejects_malformed_json() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let (status, json) = post_raw(&app.address, r#"{"protocol": "B1", "#).await;
+
+    // Assert
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(json["error"]
+        .as_str()
+        .unwrap()
+        .starts_with("Invalid request payload: "));
+}
@@ -1,7 +1,14 @@
+use std::sync::Arc;
+
 use once_cell::sync::Lazy;
 use signum_node_rs::{
     configuration::get_configuration,
-    models::datastore::Datastore,
+    crypto,
+    models::{
+        datastore::Datastore,
+        p2p::{B1Block, B1Transaction, Transaction},
+        Block,
+    },
     srs_api::SrsApiApplication,
     telemetry::{get_subscriber, init_subscriber},
 };
@@ -19,6 +26,38 @@ static TRACING: Lazy<()> = Lazy::new(|| {
     }
 });
 
+/// Returns the JSON of a block taken from mainnet.
+pub fn fixture_block() -> serde_json::Value {
+    serde_json::from_str(include_str!("../fixtures/b1_block.json"))
+        .expect("failed to parse block fixture")
+}
+
+/// The passphrase test blocks and transactions are signed with.
+pub const PASSPHRASE: &str = "integration test passphrase";
+
+/// Makes the test account the sender of a transaction and signs it.
+pub fn sign_transaction(transaction: &mut serde_json::Value) {
+    transaction["senderPublicKey"] = hex::encode(crypto::public_key(PASSPHRASE)).into();
+    let unsigned_bytes = Transaction::try_from(
+        serde_json::from_value::<B1Transaction>(transaction.clone()).unwrap(),
+    )
+    .unwrap()
+    .unsigned_bytes();
+    transaction["signature"] = hex::encode(crypto::sign(&unsigned_bytes, PASSPHRASE)).into();
+}
+
+/// Makes the test account the generator of a block and signs it.
+pub fn sign_block(block: &mut serde_json::Value) {
+    block["generatorPublicKey"] = hex::encode(crypto::public_key(PASSPHRASE)).into();
+    let unsigned_bytes = Block::try_from(serde_json::from_value::<B1Block>(block.clone()).unwrap())
+        .unwrap()
+        .unsigned_bytes();
+    block["blockSignature"] = hex::encode(crypto::sign(&unsigned_bytes, PASSPHRASE)).into();
+}
+
+/// The height the PoC+ and speedway forks are moved to by [`spawn_app`].
+pub const POC_PLUS_ENABLE: u32 = 2_000_000;
+
 pub async fn spawn_app() -> TestApp {
     Lazy::force(&TRACING);
 
@@ -33,7 +72,15 @@ pub async fn spawn_app() -> TestApp {
         c.p2p.platform = "Test".to_string();
         c.p2p.share_address = true;
         c.p2p.network_name = "TEST".to_string();
-        c.p2p.snr_reward_address = "SNRADDRESS".to_string();
+        c.p2p.snr_reward_address = Some(
+            "S-GXBA-7JP9-NR7S-DCQ4V"
+                .parse()
+                .expect("test SNR reward address should be valid"),
+        );
+        // The fixture block is from after these forks, whose rules blocks can't be checked
+        // against yet, so pushed blocks are checked as if they hadn't happened
+        c.historical_moments.poc_plus_enable = Some(POC_PLUS_ENABLE);
+        c.historical_moments.speedway_enable = Some(POC_PLUS_ENABLE);
         c
     };
 
@@ -41,9 +88,11 @@ pub async fn spawn_app() -> TestApp {
     let datastore = configuration.database.get_db().await.unwrap();
 
     // Launch the application as a background task
-    let application = SrsApiApplication::build(configuration.clone(), datastore.clone())
-        .await
-        .expect("failed to build application");
+    let historical_moments = Arc::new(configuration.historical_moments());
+    let application =
+        SrsApiApplication::build(configuration.clone(), datastore.clone(), historical_moments)
+            .await
+            .expect("failed to build application");
     let application_port = application.port();
 
     tokio::spawn(application.run_until_stopped());
@@ -56,20 +105,55 @@ pub async fn spawn_app() -> TestApp {
 
     TestApp {
         address: format!("http://localhost:{}", application_port),
-        _datastore: datastore,
-        _port: application_port,
+        datastore,
+        port: application_port,
         _api_client: client,
     }
 }
 
 pub struct TestApp {
     pub address: String,
-    pub _datastore: Datastore,
-    pub _port: u16,
+    pub datastore: Datastore,
+    pub port: u16,
     pub _api_client: reqwest::Client,
 }
 
-impl TestApp {}
+impl TestApp {
+    /// Inserts a chain of `count` copies of the fixture block, where each block's ID is
+    /// 1000 + its height and its `previousBlock` points at the block below it.
+    pub async fn insert_test_chain(&self, count: u64) {
+        for height in 0..count {
+            let mut block = fixture_block();
+            block["previousBlock"] =
+                serde_json::json!((1000 + height).saturating_sub(1).to_string());
+            block["timestamp"] = serde_json::json!(height);
+            self.datastore
+                .get_surreal_db()
+                .query(
+                    "CREATE block CONTENT { block_id: $block_id, height: $height, cumulative_difficulty: '0', block: $block }",
+                )
+                .bind(("block_id", (1000 + height).to_string()))
+                .bind(("height", height))
+                .bind(("block", block))
+                .await
+                .expect("failed to insert test block");
+        }
+    }
+
+    /// Sends a request to the app's P2P API and returns the response body.
+    pub async fn post_p2p_request(&self, body: &serde_json::Value) -> serde_json::Value {
+        reqwest::Client::new()
+            .post(format!("{}/", &self.address))
+            .header("Content-Type", "application/json")
+            .json(body)
+            .send()
+            .await
+            .expect("failed to execute request")
+            .json::<serde_json::Value>()
+            .await
+            .expect("couldn't deserialize json")
+    }
+}
 
 // async fn configure_database(configuration: &DatabaseSettings) -> Result<SqlitePool, anyhow::Error> {
 //     // Create in-memory database and migrate it
@@ -1,3 +1,6 @@
+mod blocks;
+mod errors;
 mod health_check;
 mod helpers;
 mod peers;
+mod transactions;
@@ -1,5 +1,10 @@
+use std::str::FromStr;
+
 use anyhow::Context;
-use signum_node_rs::srs_api::request_models::GetInfoRequestModel;
+use signum_node_rs::{
+    models::p2p::{PeerAddress, PeerInfo},
+    srs_api::request_models::GetInfoRequestModel,
+};
 
 use crate::helpers::spawn_app;
 
@@ -16,12 +21,12 @@ async fn srs_api_handler_returns_valid_data_for_get_info_request() -> Result<(),
         "version": "0.1.0",
         "platform": "Test",
         "shareAddress": false,
-        "networkName": "Signum-TEST",
+        "networkName": "TEST",
     });
 
     // Act
     let response = client
-        .post(&format!("{}/", &app.address))
+        .post(format!("{}/", &app.address))
         .header("Content-Type", "application/json")
         .json(&body)
         .send()
@@ -49,8 +54,120 @@ async fn srs_api_handler_returns_valid_data_for_get_info_request() -> Result<(),
 }
 
 #[tokio::test]
-#[ignore]
-async fn srs_api_handler_returns_valid_data_for_add_peers_request() {
+async fn srs_api_handler_records_and_deblacklists_peer_on_get_info_request(
+) -> Result<(), anyhow::Error> {
+    // Arrange
+    let mut app = spawn_app().await;
+
+    let client = reqwest::Client::new();
+
+    let peer = PeerAddress::from_str("localhost:8123")?;
+    app.datastore.create_new_peer(&peer).await?;
+    app.datastore.blacklist_peer(&peer).await?;
+
+    let body = serde_json::json!({
+        "requestType": "getInfo",
+        "announcedAddress": "localhost",
+        "application": "BRS",
+        "version": "3.8.2",
+        "platform": "Test",
+        "shareAddress": true,
+        "networkName": "TEST",
+    });
+
+    // Act
+    let response = client
+        .post(format!("{}/", &app.address))
+        .header("Content-Type", "application/json")
+        .json(&body)
+        .send()
+        .await
+        .expect("failed to execute request");
+
+    // Assert
+    assert!(response.status().is_success());
+
+    let mut stored = app
+        .datastore
+        .get_surreal_db()
+        .query(
+            r#"
+            SELECT application, version, ip_address, share_address, blacklist.count AS blacklist_count
+            FROM peer
+            WHERE announced_address = $peer
+        "#,
+        )
+        .bind(("peer", peer.clone()))
+        .await?;
+    let stored = stored
+        .take::<Option<serde_json::Value>>(0)?
+        .context("peer was not stored")?;
+    assert_eq!(stored["application"], "BRS");
+    assert_eq!(stored["version"], "3.8.2");
+    assert_eq!(stored["ip_address"], "127.0.0.1");
+    assert_eq!(stored["share_address"], true);
+    assert_eq!(stored["blacklist_count"], 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn srs_api_handler_ignores_get_info_request_announcing_another_address(
+) -> Result<(), anyhow::Error> {
+    // Arrange
+    let mut app = spawn_app().await;
+
+    let client = reqwest::Client::new();
+
+    let peer = PeerAddress::from_str("203.0.113.7:8123")?;
+    app.datastore.create_new_peer(&peer).await?;
+    app.datastore.blacklist_peer(&peer).await?;
+
+    let body = serde_json::json!({
+        "requestType": "getInfo",
+        "announcedAddress": "203.0.113.7",
+        "application": "BRS",
+        "version": "3.8.2",
+        "platform": "Test",
+        "shareAddress": true,
+        "networkName": "TEST",
+    });
+
+    // Act
+    let response = client
+        .post(format!("{}/", &app.address))
+        .header("Content-Type", "application/json")
+        .json(&body)
+        .send()
+        .await
+        .expect("failed to execute request");
+
+    // Assert
+    assert!(response.status().is_success());
+
+    let mut stored = app
+        .datastore
+        .get_surreal_db()
+        .query(
+            r#"
+            SELECT application, ip_address, blacklist.count AS blacklist_count
+            FROM peer
+            WHERE announced_address = $peer
+        "#,
+        )
+        .bind(("peer", peer.clone()))
+        .await?;
+    let stored = stored
+        .take::<Option<serde_json::Value>>(0)?
+        .context("peer was not stored")?;
+    assert_ne!(stored["application"], "BRS");
+    assert_ne!(stored["ip_address"], "127.0.0.1");
+    assert_eq!(stored["blacklist_count"], 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn srs_api_handler_rejects_get_info_request_from_another_network() -> Result<(), anyhow::Error>
+{
     // Arrange
     let app = spawn_app().await;
 
@@ -58,46 +175,155 @@ async fn srs_api_handler_returns_valid_data_for_add_peers_request() {
 
     let body = serde_json::json!({
         "requestType": "getInfo",
+        "announcedAddress": "othernetwork.example.com",
+        "networkName": "Signum-TEST",
     });
+
     // Act
     let response = client
-        .post(&format!("{}/", &app.address))
+        .post(format!("{}/", &app.address))
         .header("Content-Type", "application/json")
         .json(&body)
         .send()
         .await
         .expect("failed to execute request");
 
-    //TODO: Test adding new peers to database
+    // Assert
+    assert!(response.status().is_client_error());
+
+    let mut stored = app
+        .datastore
+        .get_surreal_db()
+        .query("SELECT announced_address FROM peer")
+        .await?;
+    let stored = stored.take::<Vec<PeerAddress>>("announced_address")?;
+    assert!(stored.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn srs_api_handler_returns_valid_data_for_add_peers_request() -> Result<(), anyhow::Error> {
+    // Arrange
+    let mut app = spawn_app().await;
+
+    let client = reqwest::Client::new();
+
+    let known_peer = PeerAddress::from_str("known.example.com")?;
+    app.datastore.create_new_peer(&known_peer).await?;
+
+    let body = serde_json::json!({
+        "requestType": "addPeers",
+        "peers": ["known.example.com", "new.example.com:80", "[:::1]"],
+    });
+
+    // Act
+    let response = client
+        .post(format!("{}/", &app.address))
+        .header("Content-Type", "application/json")
+        .json(&body)
+        .send()
+        .await
+        .expect("failed to execute request");
 
     // Assert
     assert!(response.status().is_success());
     assert_eq!(Some(0), response.content_length());
+
+    let mut stored = app
+        .datastore
+        .get_surreal_db()
+        .query("SELECT announced_address FROM peer ORDER BY announced_address")
+        .await?;
+    let stored = stored.take::<Vec<PeerAddress>>("announced_address")?;
+    assert_eq!(
+        stored,
+        vec![known_peer, PeerAddress::from_str("new.example.com:80")?]
+    );
+    Ok(())
 }
 
 #[tokio::test]
-#[ignore]
-async fn srs_api_handler_returns_valid_data_for_get_peers_request() {
+async fn srs_api_handler_limits_peers_handled_per_add_peers_request() -> Result<(), anyhow::Error> {
     // Arrange
     let app = spawn_app().await;
+
+    let peers = (0..150)
+        .map(|i| format!("peer{}.example.invalid", i))
+        .collect::<Vec<_>>();
+    let body = serde_json::json!({
+        "requestType": "addPeers",
+        "peers": peers,
+    });
+
+    // Act
+    let response = reqwest::Client::new()
+        .post(format!("{}/", &app.address))
+        .header("Content-Type", "application/json")
+        .json(&body)
+        .send()
+        .await
+        .expect("failed to execute request");
+
+    // Assert
+    assert!(response.status().is_success());
+
+    let mut stored = app
+        .datastore
+        .get_surreal_db()
+        .query("SELECT count() FROM peer GROUP ALL")
+        .await?;
+    let stored = stored.take::<Option<usize>>("count")?;
+    assert_eq!(stored, Some(100));
+    Ok(())
+}
+
+#[tokio::test]
+async fn srs_api_handler_returns_valid_data_for_get_peers_request() -> Result<(), anyhow::Error> {
+    // Arrange
+    let mut app = spawn_app().await;
     let client = reqwest::Client::new();
 
+    let shared_peer = PeerAddress::from_str("shared.example.com")?;
+    let unshared_peer = PeerAddress::from_str("unshared.example.com")?;
+    let unseen_peer = PeerAddress::from_str("unseen.example.com")?;
+    for (peer, share_address) in [(&shared_peer, true), (&unshared_peer, false)] {
+        app.datastore.create_new_peer(peer).await?;
+        app.datastore
+            .update_peer_info(
+                peer.clone(),
+                "127.0.0.1".to_string(),
+                PeerInfo {
+                    announced_address: Some(peer.clone()),
+                    share_address,
+                    ..Default::default()
+                },
+            )
+            .await?;
+    }
+    app.datastore.create_new_peer(&unseen_peer).await?;
+
     let body = serde_json::json!({
         "requestType": "getPeers",
     });
 
     // Act
     let response = client
-        .post(&format!("{}/", &app.address))
+        .post(format!("{}/", &app.address))
         .header("Content-Type", "application/json")
         .json(&body)
         .send()
         .await
         .expect("failed to execute request");
 
-    //TODO: Add a peer to the database in spawn_app, test against it here
-
     // Assert
     assert!(response.status().is_success());
-    assert_ne!(Some(0), response.content_length());
+    let json = response
+        .json::<serde_json::Value>()
+        .await
+        .context("couldn't deserialize json")?;
+    assert_eq!(
+        json,
+        serde_json::json!({ "peers": [shared_peer.to_string()] })
+    );
+    Ok(())
 }
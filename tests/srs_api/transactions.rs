@@ -0,0 +1,207 @@
+use std::str::FromStr;
+
+use serde_json::json;
+use signum_node_rs::{
+    models::p2p::{B1Block, PeerAddress, PeerInfo},
+    peers::{B1Peer, BasicPeerClient},
+    signum_timestamp,
+};
+
+use crate::helpers::{fixture_block, sign_transaction, spawn_app};
+
+/// Returns a signed ordinary payment of `amount_nqt` that is valid as of now.
+fn transaction(amount_nqt: u64) -> serde_json::Value {
+    let mut transaction = json!({
+        "type": 0,
+        "subtype": 0,
+        "timestamp": signum_timestamp() - 60,
+        "deadline": 60,
+        "senderPublicKey": "7347ae2ec9aaa0a93f90a1c394c370a629227928ba2f24843634c81358d2b403",
+        "recipient": "13657951110994294056",
+        "amountNQT": amount_nqt.to_string(),
+        "feeNQT": "1000000",
+        "signature": "00".repeat(64),
+        "version": 2,
+        "ecBlockId": "6079409147758822541",
+        "ecBlockHeight": 1237188,
+        "cashBackId": "13420738867631717395"
+    });
+    sign_transaction(&mut transaction);
+    transaction
+}
+
+#[tokio::test]
+async fn process_transactions_adds_new_transactions_to_the_pool() {
+    // Arrange
+    let app = spawn_app().await;
+    let duplicated = transaction(1_000_000);
+
+    // Act
+    let json = app
+        .post_p2p_request(&json!({
+            "protocol": "B1",
+            "requestType": "processTransactions",
+            "transactions": [duplicated, transaction(2_000_000), duplicated]
+        }))
+        .await;
+
+    // Assert
+    assert_eq!(json, json!({}));
+    let json = app
+        .post_p2p_request(&json!({ "requestType": "getUnconfirmedTransactions" }))
+        .await;
+    let transactions = json["unconfirmedTransactions"].as_array().unwrap();
+    assert_eq!(transactions.len(), 2);
+    let mut amounts = transactions
+        .iter()
+        .map(|t| t["amountNQT"].as_u64().unwrap())
+        .collect::<Vec<_>>();
+    amounts.sort();
+    assert_eq!(amounts, [1_000_000, 2_000_000]);
+}
+
+#[tokio::test]
+async fn process_transactions_skips_transactions_already_in_the_chain() -> Result<(), anyhow::Error>
+{
+    // Arrange
+    let app = spawn_app().await;
+    let confirmed = transaction(1_000_000);
+    let mut block = fixture_block();
+    block["transactions"] = json!([confirmed]);
+    app.datastore
+        .store_block(
+            &serde_json::from_value::<B1Block>(block)?,
+            1,
+            1,
+            &0u8.into(),
+        )
+        .await?;
+
+    // Act
+    let json = app
+        .post_p2p_request(&json!({
+            "protocol": "B1",
+            "requestType": "processTransactions",
+            "transactions": [confirmed, transaction(2_000_000)]
+        }))
+        .await;
+
+    // Assert
+    assert_eq!(json, json!({}));
+    let json = app
+        .post_p2p_request(&json!({ "requestType": "getUnconfirmedTransactions" }))
+        .await;
+    let transactions = json["unconfirmedTransactions"].as_array().unwrap();
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(transactions[0]["amountNQT"], 2_000_000);
+    Ok(())
+}
+
+#[tokio::test]
+async fn get_unconfirmed_transactions_skips_expired_transactions() -> Result<(), anyhow::Error> {
+    // Arrange
+    let app = spawn_app().await;
+    let mut expired = transaction(3_000_000);
+    expired["timestamp"] = json!(signum_timestamp() - 3601);
+    app.datastore
+        .add_unconfirmed_transaction(&serde_json::from_value(expired)?)
+        .await?;
+    app.datastore
+        .add_unconfirmed_transaction(&serde_json::from_value(transaction(1_000_000))?)
+        .await?;
+
+    // Act
+    let json = app
+        .post_p2p_request(&json!({ "requestType": "getUnconfirmedTransactions" }))
+        .await;
+
+    // Assert
+    let transactions = json["unconfirmedTransactions"].as_array().unwrap();
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(transactions[0]["amountNQT"], 1_000_000);
+    Ok(())
+}
+
+#[tokio::test]
+async fn process_transactions_rejects_invalid_transactions_and_blacklists_sender(
+) -> Result<(), anyhow::Error> {
+    // Arrange
+    let mut app = spawn_app().await;
+    let peer = PeerAddress::from_str("sender.example.com")?;
+    app.datastore.create_new_peer(&peer).await?;
+    app.datastore
+        .update_peer_info(
+            peer.clone(),
+            "127.0.0.1".to_string(),
+            PeerInfo {
+                announced_address: Some(peer.clone()),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+    let mut low_fee = transaction(2_000_000);
+    low_fee["feeNQT"] = json!("1");
+
+    // Act
+    app.post_p2p_request(&json!({
+        "requestType": "processTransactions",
+        "transactions": [low_fee, transaction(1_000_000)]
+    }))
+    .await;
+
+    // Assert
+    let json = app
+        .post_p2p_request(&json!({ "requestType": "getUnconfirmedTransactions" }))
+        .await;
+    assert_eq!(json["unconfirmedTransactions"], json!([]));
+    let mut blacklist = app
+        .datastore
+        .get_surreal_db()
+        .query(
+            "SELECT VALUE blacklist.count FROM ONLY peer WHERE announced_address = $peer LIMIT 1",
+        )
+        .bind(("peer", peer))
+        .await?;
+    assert_eq!(blacklist.take::<Option<u64>>(0)?, Some(1));
+    Ok(())
+}
+
+#[tokio::test]
+async fn process_transactions_rejects_transactions_with_invalid_signatures() {
+    // Arrange
+    let app = spawn_app().await;
+    let mut forged = transaction(1_000_000);
+    forged["amountNQT"] = json!("2000000");
+
+    // Act
+    app.post_p2p_request(&json!({
+        "requestType": "processTransactions",
+        "transactions": [forged]
+    }))
+    .await;
+
+    // Assert
+    let json = app
+        .post_p2p_request(&json!({ "requestType": "getUnconfirmedTransactions" }))
+        .await;
+    assert_eq!(json["unconfirmedTransactions"], json!([]));
+}
+
+#[tokio::test]
+async fn b1_peer_gets_unconfirmed_transactions() -> Result<(), anyhow::Error> {
+    // Arrange
+    let app = spawn_app().await;
+    app.datastore
+        .add_unconfirmed_transaction(&serde_json::from_value(transaction(1_000_000))?)
+        .await?;
+    let peer = B1Peer::new(PeerAddress::from_str(&format!("localhost:{}", app.port))?);
+
+    // Act
+    let transactions = peer.get_unconfirmed_transactions().await?;
+
+    // Assert
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(transactions[0].fee_nqt, 1_000_000);
+    Ok(())
+}